@@ -2,6 +2,7 @@ use std::{
     env, fs,
     path::{Path, PathBuf},
     process::Command,
+    sync::{Mutex, OnceLock},
 };
 
 // --- diagnostics -------------------------------------------------------------
@@ -30,12 +31,168 @@ fn dump_env(keys: &[&str]) {
     }
 }
 
+// --- cacheable build config ---------------------------------------------------
+//
+// Following the approach Zig's `zig libc` file takes for caching detected
+// libc paths: when detection (sysroot probing, pkg-config, directory walks)
+// succeeds, everything it found is written once as plain `key=value` lines
+// to the path in `READSTAT_CONFIG_FILE`. On later builds that file is loaded
+// first and its (still-existing) paths are used directly, skipping the
+// `Command::new("...-gcc")` probes, pkg-config calls, and `find_readstat_dir`
+// directory walk entirely. Each field degrades independently: a stale path
+// for one field falls back to live detection for just that field rather
+// than invalidating the whole file.
+
+#[derive(Default, Clone)]
+struct BuildConfig {
+    target: Option<String>,
+    readstat_dir: Option<PathBuf>,
+    sysroot: Option<PathBuf>,
+    zlib_include: Option<PathBuf>,
+    zlib_lib_dir: Option<PathBuf>,
+    zlib_link_kind: Option<String>,
+    libclang_path: Option<PathBuf>,
+}
+
+impl BuildConfig {
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        let mut line = |k: &str, v: &Option<String>| {
+            if let Some(v) = v {
+                out.push_str(k);
+                out.push('=');
+                out.push_str(v);
+                out.push('\n');
+            }
+        };
+        line("target", &self.target);
+        line("readstat_dir", &self.readstat_dir.as_ref().map(|p| p.display().to_string()));
+        line("sysroot", &self.sysroot.as_ref().map(|p| p.display().to_string()));
+        line("zlib_include", &self.zlib_include.as_ref().map(|p| p.display().to_string()));
+        line("zlib_lib_dir", &self.zlib_lib_dir.as_ref().map(|p| p.display().to_string()));
+        line("zlib_link_kind", &self.zlib_link_kind);
+        line("libclang_path", &self.libclang_path.as_ref().map(|p| p.display().to_string()));
+        out
+    }
+
+    fn from_text(text: &str) -> Self {
+        let mut cfg = BuildConfig::default();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "target" => cfg.target = Some(value.to_string()),
+                "readstat_dir" => cfg.readstat_dir = Some(PathBuf::from(value)),
+                "sysroot" => cfg.sysroot = Some(PathBuf::from(value)),
+                "zlib_include" => cfg.zlib_include = Some(PathBuf::from(value)),
+                "zlib_lib_dir" => cfg.zlib_lib_dir = Some(PathBuf::from(value)),
+                "zlib_link_kind" => cfg.zlib_link_kind = Some(value.to_string()),
+                "libclang_path" => cfg.libclang_path = Some(PathBuf::from(value)),
+                _ => {}
+            }
+        }
+        cfg
+    }
+}
+
+fn config_file_path() -> Option<PathBuf> {
+    env::var_os("READSTAT_CONFIG_FILE").map(PathBuf::from)
+}
+
+fn load_config_file(path: &Path) -> Option<BuildConfig> {
+    match fs::read_to_string(path) {
+        Ok(text) => {
+            diag!("Loaded cached build config from {}", path.display());
+            Some(BuildConfig::from_text(&text))
+        }
+        Err(e) => {
+            diag!("No usable cached build config at {} ({e})", path.display());
+            None
+        }
+    }
+}
+
+fn save_config_file(path: &Path, cfg: &BuildConfig) {
+    if let Err(e) = fs::write(path, cfg.to_text()) {
+        diag!("Failed to write build config cache to {}: {e}", path.display());
+    } else {
+        diag!("Wrote build config cache to {}", path.display());
+    }
+}
+
+/// Parses `path` independently of the rest of detection and reports, via
+/// `cargo:warning`, which recorded paths are missing or stale. Does not
+/// affect linking: callers still fall back to live detection per-field
+/// regardless of what this reports.
+fn validate_config_file(path: &Path) {
+    let Some(cfg) = load_config_file(path) else {
+        println!("cargo:warning=READSTAT_CONFIG_VALIDATE: no config file at {}", path.display());
+        return;
+    };
+    let mut stale = Vec::new();
+    for (name, p) in [
+        ("readstat_dir", &cfg.readstat_dir),
+        ("sysroot", &cfg.sysroot),
+        ("zlib_include", &cfg.zlib_include),
+        ("zlib_lib_dir", &cfg.zlib_lib_dir),
+        ("libclang_path", &cfg.libclang_path),
+    ] {
+        if let Some(p) = p {
+            if !p.exists() {
+                stale.push(format!("{name}={}", p.display()));
+            }
+        }
+    }
+    if stale.is_empty() {
+        println!(
+            "cargo:warning=READSTAT_CONFIG_VALIDATE: all recorded paths present in {}",
+            path.display()
+        );
+    } else {
+        println!(
+            "cargo:warning=READSTAT_CONFIG_VALIDATE: stale/missing paths in {}: {}",
+            path.display(),
+            stale.join(", ")
+        );
+    }
+}
+
+static CACHED_CONFIG: OnceLock<Option<BuildConfig>> = OnceLock::new();
+static DISCOVERED_CONFIG: OnceLock<Mutex<BuildConfig>> = OnceLock::new();
+
+fn cached_config() -> Option<&'static BuildConfig> {
+    CACHED_CONFIG.get().and_then(|o| o.as_ref())
+}
+
+fn discovered_config() -> &'static Mutex<BuildConfig> {
+    DISCOVERED_CONFIG.get_or_init(|| Mutex::new(BuildConfig::default()))
+}
+
+fn record_discovered(f: impl FnOnce(&mut BuildConfig)) {
+    f(&mut discovered_config().lock().unwrap());
+}
+
 // --- bindgen ----------------------------------------------------------------
 
 fn detect_sysroot_for_target(target: &str) -> Option<PathBuf> {
+    if let Some(cached) = cached_config().and_then(|c| c.sysroot.clone()) {
+        if cached.exists() {
+            diag!("Using cached sysroot {}", cached.display());
+            return Some(cached);
+        }
+        diag!("Cached sysroot {} no longer exists; re-detecting", cached.display());
+    }
+
     if let Ok(p) = env::var("BINDGEN_SYSROOT") {
         let p = PathBuf::from(p);
         if p.exists() {
+            record_discovered(|c| c.sysroot = Some(p.clone()));
             return Some(p);
         }
     }
@@ -50,6 +207,7 @@ fn detect_sysroot_for_target(target: &str) -> Option<PathBuf> {
                 let s = String::from_utf8_lossy(&out.stdout).trim().to_string();
                 if !s.is_empty() && Path::new(&s).exists() {
                     diag!("Detected sysroot from {cc}: {s}");
+                    record_discovered(|c| c.sysroot = Some(PathBuf::from(&s)));
                     return Some(PathBuf::from(s));
                 }
             }
@@ -58,6 +216,41 @@ fn detect_sysroot_for_target(target: &str) -> Option<PathBuf> {
     None
 }
 
+/// Maps a Rust `TARGET` triple to the GNU multiarch tuple Debian/Ubuntu-style
+/// sysroots use for their per-arch `/usr/include/<tuple>` and
+/// `/usr/lib/<tuple>` subdirectories, mirroring the triple-driven sysroot
+/// construction cranelift's `build_sysroot` logic uses. Unlike hardcoding
+/// `aarch64-linux-gnu`/`x86_64-linux-gnu`, this derives the tuple from the
+/// triple's arch/env components so armv7, riscv64, powerpc64le, s390x, musl,
+/// and other targets get a candidate tuple without a new branch here; an
+/// unrecognized arch is passed through unchanged rather than guessed at.
+fn multiarch_tuple(target: &str) -> Option<String> {
+    if !target.contains("linux") {
+        return None;
+    }
+    let arch = target.split('-').next()?;
+    let env = target.rsplit('-').next()?;
+
+    let debian_arch = match arch {
+        "aarch64" | "aarch64_be" => "aarch64",
+        "x86_64" => "x86_64",
+        "i386" | "i586" | "i686" => "i386",
+        "arm" | "armv5te" | "armv6" | "armv7" | "thumbv7neon" => "arm",
+        "riscv64gc" | "riscv64" => "riscv64",
+        "powerpc64le" => "powerpc64le",
+        "powerpc64" => "powerpc64",
+        "powerpc" => "powerpc",
+        "s390x" => "s390x",
+        "mips64el" => "mips64el",
+        "mips64" => "mips64",
+        "mipsel" => "mipsel",
+        "mips" => "mips",
+        other => other,
+    };
+
+    Some(format!("{debian_arch}-linux-{env}"))
+}
+
 fn bindgen_with_includes(include_dir: &Path) {
     let target = env::var("TARGET").unwrap_or_default();
     let host = env::var("HOST").unwrap_or_default();
@@ -75,19 +268,11 @@ fn bindgen_with_includes(include_dir: &Path) {
             builder = builder
                 .clang_arg(format!("--sysroot={}", sysroot.display()))
                 .clang_arg(format!("-I{}/usr/include", sysroot.display()));
-            let trip = if target.starts_with("aarch64") {
-                "aarch64-linux-gnu"
-            } else if target.starts_with("x86_64") {
-                "x86_64-linux-gnu"
-            } else {
-                ""
-            };
-            if !trip.is_empty() {
-                builder = builder.clang_arg(format!(
-                    "-I{}/usr/include/{}",
-                    sysroot.display(),
-                    trip
-                ));
+            if let Some(tuple) = multiarch_tuple(&target) {
+                let multiarch_include = sysroot.join("usr/include").join(&tuple);
+                if multiarch_include.exists() {
+                    builder = builder.clang_arg(format!("-I{}", multiarch_include.display()));
+                }
             }
             diag!("bindgen using sysroot {}", sysroot.display());
         }
@@ -105,9 +290,18 @@ fn bindgen_with_includes(include_dir: &Path) {
 // --- locating ReadStat sources ----------------------------------------------
 
 fn find_readstat_dir() -> Option<PathBuf> {
+    if let Some(cached) = cached_config().and_then(|c| c.readstat_dir.clone()) {
+        if cached.join("src/readstat.h").exists() {
+            diag!("Using cached ReadStat source dir {}", cached.display());
+            return Some(cached);
+        }
+        diag!("Cached ReadStat dir {} no longer valid; re-searching", cached.display());
+    }
+
     if let Some(p) = env::var_os("READSTAT_SRC") {
         let p = PathBuf::from(p);
         if p.join("src/readstat.h").exists() {
+            record_discovered(|c| c.readstat_dir = Some(p.clone()));
             return Some(p);
         }
     }
@@ -116,10 +310,12 @@ fn find_readstat_dir() -> Option<PathBuf> {
     for _ in 0..6 {
         let third_party = cur.join("native/readstat-sys/third_party/readstat");
         if third_party.join("src/readstat.h").exists() {
+            record_discovered(|c| c.readstat_dir = Some(third_party.clone()));
             return Some(third_party);
         }
         let readstat_top = cur.join("ReadStat");
         if readstat_top.join("src/readstat.h").exists() {
+            record_discovered(|c| c.readstat_dir = Some(readstat_top.clone()));
             return Some(readstat_top);
         }
         if !cur.pop() {
@@ -129,40 +325,107 @@ fn find_readstat_dir() -> Option<PathBuf> {
     None
 }
 
+// --- static/dynamic link mode ------------------------------------------------
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum LinkMode {
+    Static,
+    Dynamic,
+}
+
+impl LinkMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            LinkMode::Static => "static",
+            LinkMode::Dynamic => "dynamic",
+        }
+    }
+
+    /// rustc-link-lib kind prefix: `static=` or empty (dynamic is the
+    /// implicit default rustc-link-lib kind).
+    fn lib_kind(self) -> &'static str {
+        match self {
+            LinkMode::Static => "static=",
+            LinkMode::Dynamic => "",
+        }
+    }
+}
+
+/// Single source of truth for whether ReadStat and zlib are linked
+/// statically or dynamically, mirroring rustc's own rlib/dylib +
+/// `prefer-dynamic` distinction. `READSTAT_LINK=static|dylib` (or
+/// `dynamic`) takes precedence; otherwise the `static`/`dynamic` cargo
+/// features select it; the historical default (dynamic) is kept when
+/// neither is set so existing builds don't change behavior.
+fn link_mode() -> LinkMode {
+    if let Ok(v) = env::var("READSTAT_LINK") {
+        return match v.as_str() {
+            "static" => LinkMode::Static,
+            "dylib" | "dynamic" => LinkMode::Dynamic,
+            other => panic!("READSTAT_LINK must be 'static' or 'dylib'/'dynamic', got '{other}'"),
+        };
+    }
+    if cfg!(feature = "static") {
+        return LinkMode::Static;
+    }
+    if cfg!(feature = "dynamic") {
+        return LinkMode::Dynamic;
+    }
+    LinkMode::Dynamic
+}
+
 // --- zlib detection / configuration -----------------------------------------
 
-fn link_static_z_from_dir(dir: &Path) {
+fn link_z_from_dir(dir: &Path, mode: LinkMode) {
     println!("cargo:rustc-link-search=native={}", dir.display());
     #[cfg(target_os = "windows")]
     {
         let z = dir.join("z.lib");
         let zstatic = dir.join("zlibstatic.lib");
-        if zstatic.exists() {
-            println!("cargo:rustc-link-lib=static=zlibstatic");
-        } else if z.exists() {
-            println!("cargo:rustc-link-lib=static=z");
+        let name = if mode == LinkMode::Static && zstatic.exists() {
+            "zlibstatic"
         } else {
-            println!("cargo:rustc-link-lib=static=z");
-        }
+            let _ = z;
+            "z"
+        };
+        println!("cargo:rustc-link-lib={}{}", mode.lib_kind(), name);
     }
     #[cfg(not(target_os = "windows"))]
     {
-        println!("cargo:rustc-link-lib=static=z");
+        println!("cargo:rustc-link-lib={}z", mode.lib_kind());
     }
 }
 
 /// Configure zlib; enable ONLY if we have headers/paths.
 /// Prefer DEP_Z_* (from libz-sys dependency), then pkg-config, then sysroot probe.
-fn configure_zlib(build: &mut cc::Build) -> bool {
+fn configure_zlib(build: &mut cc::Build, mode: LinkMode) -> bool {
     if let Ok(v) = env::var("READSTAT_WITH_ZLIB") {
         let on = v != "0";
         if on {
-            println!("cargo:rustc-link-lib=z");
+            println!("cargo:rustc-link-lib={}z", mode.lib_kind());
         }
         diag!("READSTAT_WITH_ZLIB override -> {}", if on { "ON" } else { "OFF" });
         return on;
     }
 
+    // Cached from a prior run's detection, skipping pkg-config/sysroot probes
+    // entirely as long as the recorded paths still exist.
+    if let Some(cfg) = cached_config() {
+        if let (Some(lib_dir), Some(kind)) = (&cfg.zlib_lib_dir, &cfg.zlib_link_kind) {
+            let include_ok = cfg.zlib_include.as_ref().map(|p| p.exists()).unwrap_or(true);
+            if lib_dir.exists() && include_ok {
+                let cached_mode = if kind == "static" { LinkMode::Static } else { LinkMode::Dynamic };
+                diag!("Using cached zlib config: lib_dir={}, kind={}", lib_dir.display(), kind);
+                if let Some(inc) = &cfg.zlib_include {
+                    build.include(inc);
+                }
+                link_z_from_dir(lib_dir, cached_mode);
+                return true;
+            }
+            diag!("Cached zlib paths no longer valid; re-detecting");
+        }
+    }
+
     // 0) libz-sys (target) exports
     let dep_z_include = env::var("DEP_Z_INCLUDE").ok();
     let dep_z_root = env::var("DEP_Z_ROOT").ok();
@@ -174,13 +437,15 @@ fn configure_zlib(build: &mut cc::Build) -> bool {
         }
         if let Some(lib) = dep_z_lib.as_deref() {
             diag!("Using zlib lib dir from DEP_Z_LIB={lib}");
-            link_static_z_from_dir(Path::new(lib));
+            link_z_from_dir(Path::new(lib), mode);
+            record_discovered(|c| c.zlib_lib_dir = Some(PathBuf::from(lib)));
         } else if let Some(root) = dep_z_root.as_deref() {
             for cand in ["lib", "lib64", ""].iter() {
                 let p = Path::new(root).join(cand);
                 if p.exists() {
                     diag!("Using zlib lib dir {}", p.display());
-                    link_static_z_from_dir(&p);
+                    link_z_from_dir(&p, mode);
+                    record_discovered(|c| c.zlib_lib_dir = Some(p.clone()));
                     break;
                 }
             }
@@ -188,15 +453,24 @@ fn configure_zlib(build: &mut cc::Build) -> bool {
                 let inc = Path::new(root).join("include");
                 if inc.exists() {
                     diag!("Using zlib headers from {}", inc.display());
-                    build.include(inc);
+                    build.include(&inc);
+                    record_discovered(|c| c.zlib_include = Some(inc.clone()));
                 }
             }
         }
+        if let Some(inc) = dep_z_include.as_deref() {
+            record_discovered(|c| c.zlib_include = Some(PathBuf::from(inc)));
+        }
+        record_discovered(|c| c.zlib_link_kind = Some(mode.as_str().to_string()));
         return true;
     }
 
     // 1) pkg-config (native)
-    if let Ok(lib) = pkg_config::Config::new().env_metadata(true).probe("zlib") {
+    if let Ok(lib) = pkg_config::Config::new()
+        .env_metadata(true)
+        .statik(mode == LinkMode::Static)
+        .probe("zlib")
+    {
         diag!("Found zlib via pkg-config");
         for p in &lib.include_paths {
             diag!("  zlib include: {}", p.display());
@@ -206,31 +480,62 @@ fn configure_zlib(build: &mut cc::Build) -> bool {
             diag!("  zlib link:    {}", p.display());
             println!("cargo:rustc-link-search=native={}", p.display());
         }
-        println!("cargo:rustc-link-lib=z");
+        println!("cargo:rustc-link-lib={}z", mode.lib_kind());
+        record_discovered(|c| {
+            c.zlib_include = lib.include_paths.first().cloned();
+            c.zlib_lib_dir = lib.link_paths.first().cloned();
+            c.zlib_link_kind = Some(mode.as_str().to_string());
+        });
         return true;
     }
 
-    // 2) sysroot probe (cross or native): only enable if header actually exists
+    // 2) sysroot probe (cross or native): only enable if header actually exists.
+    // The multiarch include/lib subdirectory is derived from the target
+    // triple via `multiarch_tuple` rather than two hardcoded arches, so
+    // armv7/riscv64/powerpc64le/s390x/musl sysroots are found the same way.
     let target = env::var("TARGET").unwrap_or_default();
     let _host = env::var("HOST").unwrap_or_default();
     if let Some(sysroot) = detect_sysroot_for_target(&target) {
         let base = sysroot.join("usr/include");
-        let trip = if target.starts_with("aarch64") {
-            base.join("aarch64-linux-gnu")
-        } else if target.starts_with("x86_64") {
-            base.join("x86_64-linux-gnu")
-        } else {
-            PathBuf::new()
-        };
+        let lib_base = sysroot.join("usr/lib");
+        let tuple = multiarch_tuple(&target);
+        let multiarch_include = tuple.as_deref().map(|t| base.join(t));
+        let multiarch_lib = tuple.as_deref().map(|t| lib_base.join(t));
+
         let candidates = [
             base.join("zlib.h"),
-            if trip.as_os_str().is_empty() { PathBuf::new() } else { trip.join("zlib.h") },
+            multiarch_include
+                .as_ref()
+                .map(|p| p.join("zlib.h"))
+                .unwrap_or_default(),
         ];
-        if candidates.iter().any(|p| p.exists()) {
-            if base.exists() { build.include(&base); }
-            if !trip.as_os_str().is_empty() && trip.exists() { build.include(&trip); }
-            println!("cargo:rustc-link-lib=z");
+        if candidates.iter().any(|p| !p.as_os_str().is_empty() && p.exists()) {
+            if base.exists() {
+                build.include(&base);
+            }
+            if let Some(inc) = &multiarch_include {
+                if inc.exists() {
+                    build.include(inc);
+                }
+            }
+            // The multiarch lib dir (e.g. <sysroot>/usr/lib/<tuple>) is where
+            // cross sysroots actually keep libz.so/.a; without this search
+            // path the link step silently falls back to host library dirs.
+            let link_dir = multiarch_lib
+                .as_ref()
+                .filter(|p| p.exists())
+                .cloned()
+                .unwrap_or_else(|| lib_base.clone());
+            if link_dir.exists() {
+                println!("cargo:rustc-link-search=native={}", link_dir.display());
+            }
+            println!("cargo:rustc-link-lib={}z", mode.lib_kind());
             diag!("Using zlib from sysroot {}", sysroot.display());
+            record_discovered(|c| {
+                c.zlib_include = multiarch_include.clone().or_else(|| Some(base.clone()));
+                c.zlib_lib_dir = Some(link_dir.clone());
+                c.zlib_link_kind = Some(mode.as_str().to_string());
+            });
             return true;
         }
     }
@@ -242,7 +547,7 @@ fn configure_zlib(build: &mut cc::Build) -> bool {
 
 // --- build vendored ReadStat -------------------------------------------------
 
-fn build_vendored(rs_dir: &Path) {
+fn build_vendored(rs_dir: &Path, mode: LinkMode) {
     let src_dir = rs_dir.join("src");
     let inc_dir = rs_dir.join("src");
     assert!(
@@ -295,7 +600,7 @@ fn build_vendored(rs_dir: &Path) {
     build.define("HAVE_STRINGS_H", Some("1"));
 
     // zlib detection
-    let has_zlib = configure_zlib(&mut build);
+    let has_zlib = configure_zlib(&mut build, mode);
     if has_zlib {
         build.define("READSTAT_HAVE_ZLIB", Some("1"));
         build.define("HAVE_ZLIB", Some("1"));
@@ -414,16 +719,19 @@ readstat_io_t* unistd_io_init(void) { return NULL; }
 
 // --- non-vendored link paths -------------------------------------------------
 
-fn link_from_prefix(prefix: &str) {
+fn link_from_prefix(prefix: &str, mode: LinkMode) {
     println!("cargo:rustc-link-search=native={prefix}/lib");
-    println!("cargo:rustc-link-lib=readstat");
-    println!("cargo:rustc-link-lib=z");
+    println!("cargo:rustc-link-lib={}readstat", mode.lib_kind());
+    println!("cargo:rustc-link-lib={}z", mode.lib_kind());
     println!("cargo:include={prefix}/include");
     bindgen_with_includes(&PathBuf::from(format!("{prefix}/include")));
 }
 
-fn link_from_pkg_config() -> bool {
-    match pkg_config::Config::new().probe("readstat") {
+fn link_from_pkg_config(mode: LinkMode) -> bool {
+    match pkg_config::Config::new()
+        .statik(mode == LinkMode::Static)
+        .probe("readstat")
+    {
         Ok(lib) => {
             if let Some(inc) = lib.include_paths.get(0) {
                 bindgen_with_includes(inc);
@@ -444,14 +752,62 @@ fn main() {
         "READSTAT_BUILD_DEBUG","READSTAT_SRC","READSTAT_WITH_ZLIB","READSTAT_PREFIX",
         "PKG_CONFIG","PKG_CONFIG_PATH","PKG_CONFIG_SYSROOT_DIR","PKG_CONFIG_LIBDIR",
         "LIBCLANG_PATH","PKG_CONFIG_ALLOW_CROSS","DEP_Z_INCLUDE","DEP_Z_ROOT",
-        "DEP_Z_LIB","BINDGEN_SYSROOT","ZLIB_NO_PKG_CONFIG"
+        "DEP_Z_LIB","BINDGEN_SYSROOT","ZLIB_NO_PKG_CONFIG","READSTAT_LINK",
+        "READSTAT_CONFIG_FILE","READSTAT_CONFIG_VALIDATE",
     ] {
         println!("cargo:rerun-if-env-changed={k}");
     }
 
+    let target = env::var("TARGET").unwrap_or_default();
+    let config_path = config_file_path();
+
+    if debug_on() || matches!(env::var("READSTAT_CONFIG_VALIDATE").as_deref(), Ok("1") | Ok("true") | Ok("yes") | Ok("on")) {
+        if let Some(p) = &config_path {
+            validate_config_file(p);
+        }
+    }
+
+    let had_cache = config_path
+        .as_deref()
+        .and_then(load_config_file)
+        .map(|cfg| {
+            CACHED_CONFIG.set(Some(cfg)).ok();
+            true
+        })
+        .unwrap_or_else(|| {
+            CACHED_CONFIG.set(None).ok();
+            false
+        });
+
+    // Persists whatever `record_discovered` collected during this run, but
+    // only when there was no usable cache to begin with -- a file full of
+    // live-detected paths is written once and then just reused.
+    let save_cache = || {
+        if had_cache {
+            return;
+        }
+        if let Some(path) = &config_path {
+            record_discovered(|c| {
+                c.target = Some(target.clone());
+                if let Ok(p) = env::var("LIBCLANG_PATH") {
+                    c.libclang_path = Some(PathBuf::from(p));
+                }
+            });
+            let cfg = discovered_config().lock().unwrap().clone();
+            save_config_file(path, &cfg);
+        }
+    };
+
+    let mode = link_mode();
+    // Exposed as DEP_READSTAT_LINK_MODE to dependent crates (requires this
+    // crate's `links = "readstat"` manifest key for cargo to route it).
+    println!("cargo:link_mode={}", mode.as_str());
+    diag!("Link mode: {}", mode.as_str());
+
     if cfg!(feature = "vendored") {
         if let Some(dir) = find_readstat_dir() {
-            build_vendored(&dir);
+            build_vendored(&dir, mode);
+            save_cache();
             return;
         }
         panic!(
@@ -462,9 +818,20 @@ fn main() {
         );
     }
 
-    if link_from_pkg_config() { return; }
-    if let Ok(prefix) = env::var("READSTAT_PREFIX") { link_from_prefix(&prefix); return; }
-    if let Ok(home) = env::var("HOME") { link_from_prefix(&format!("{home}/.local")); return; }
+    if link_from_pkg_config(mode) {
+        save_cache();
+        return;
+    }
+    if let Ok(prefix) = env::var("READSTAT_PREFIX") {
+        link_from_prefix(&prefix, mode);
+        save_cache();
+        return;
+    }
+    if let Ok(home) = env::var("HOME") {
+        link_from_prefix(&format!("{home}/.local"), mode);
+        save_cache();
+        return;
+    }
 
     panic!(
         "Unable to locate ReadStat.\n\
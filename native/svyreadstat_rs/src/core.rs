@@ -1,7 +1,7 @@
 // native/svyreadstat_rs/src/core.rs
 use anyhow::Result;
-use arrow::array::{ArrayRef, Float64Builder, StringBuilder};
-use arrow::datatypes::{DataType, Field, Schema};
+use arrow::array::{Array, ArrayRef, Float64Builder, StringBuilder, StringDictionaryBuilder};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema};
 use arrow::ipc::writer::FileWriter;
 use arrow::record_batch::RecordBatch;
 use serde::Serialize;
@@ -13,6 +13,7 @@ use std::sync::Arc;
 use readstat_sys::{
     readstat_double_value,
     readstat_get_file_label,
+    readstat_get_row_count,
     readstat_metadata_t,
     readstat_string_value,
     readstat_type_class_e_READSTAT_TYPE_CLASS_STRING as TCLASS_STRING,
@@ -32,6 +33,35 @@ use readstat_sys::{
     readstat_variable_t,
 };
 
+/// Wraps an Arrow IPC decode error (from reading a `RecordBatch` out of a
+/// `FileReader`/`StreamReader`) with an actionable hint when the failure
+/// looks like a missing compression codec. The write-side readers are built
+/// with `IpcReadOptions::default()` so LZ4_FRAME/ZSTD-compressed bodies
+/// decode transparently, but that only holds if the `arrow`/`arrow-ipc`
+/// dependency is itself compiled with its "lz4"/"zstd" Cargo features.
+///
+/// This crate has no `Cargo.toml` checked in, so that feature list cannot be
+/// declared, inspected, or confirmed from inside this source tree — whoever
+/// adds the manifest for this crate must turn `arrow`'s "lz4" and "zstd"
+/// features on for compressed IPC bodies to actually decode; until then,
+/// this function is the best available mitigation, turning the failure into
+/// a diagnosable error instead of an opaque one.
+pub(crate) fn describe_ipc_decode_err(err: arrow::error::ArrowError) -> anyhow::Error {
+    let msg = err.to_string();
+    let lower = msg.to_ascii_lowercase();
+    if lower.contains("compress") || lower.contains("lz4") || lower.contains("zstd") || lower.contains("codec")
+    {
+        anyhow::anyhow!(
+            "failed to decode Arrow IPC batch: {msg} (this looks like a compressed IPC body; \
+             decoding it requires the `arrow`/`arrow-ipc` dependency to be built with its \
+             \"lz4\" and \"zstd\" Cargo features enabled — check the workspace manifest's \
+             feature list for the `arrow` dependency)"
+        )
+    } else {
+        anyhow::anyhow!("failed to decode Arrow IPC batch: {msg}")
+    }
+}
+
 pub(crate) const HANDLER_OK: c_int = 0;
 pub(crate) const HANDLER_ABORT: c_int = 1;
 
@@ -113,12 +143,33 @@ pub(crate) struct ParseCtx {
     pub(crate) n_max: Option<usize>,
     pub(crate) n_rows_seen: usize,
     pub(crate) n_rows_emitted: usize,
+    /// The last file-row index a value callback fired for, used to count
+    /// `n_rows_emitted` off row advancement rather than off a specific
+    /// column — a skipped first column would otherwise mean no row ever
+    /// reaches that check. `None` until the first row in range is seen.
+    pub(crate) last_emitted_row: Option<usize>,
     pub(crate) label_sets: HashMap<String, BTreeMap<String, String>>,
     pub(crate) file_label: Option<String>,
     pub(crate) last_err: Option<String>,
     pub(crate) tagged: HashMap<String, (Vec<usize>, Vec<String>)>,
     pub(crate) notes: Vec<String>,
     pub(crate) detect_tagged: bool,
+    /// SPSS user-defined missing values (discrete codes / ranges): when
+    /// `false`, values flagged by `readstat_value_is_defined_missing` are
+    /// emitted as Arrow nulls; when `true`, the underlying value is kept and
+    /// the missing-value definitions captured in `ColBuilders::user_missing`
+    /// round-trip into `VarMeta` instead.
+    pub(crate) user_na: bool,
+    /// Row count from the file's metadata header, filled in by
+    /// `on_metadata_cb`; used to pre-size each column's Arrow builder so we
+    /// don't repeatedly reallocate while streaming values.
+    pub(crate) row_capacity: Option<usize>,
+    /// When `true`, `finalize_to_ipc` materializes any numeric column that
+    /// has an associated value-label set as an Arrow `DictionaryArray` of
+    /// label strings (falling back to the stringified code for unlabeled
+    /// values) instead of a plain `Float64` column, so callers get a
+    /// first-class categorical straight off the wire.
+    pub(crate) apply_value_labels: bool,
 }
 
 /// ---------- Helpers on builders ----------
@@ -150,6 +201,10 @@ extern "C" {
         value: readstat_sys::readstat_value_t,
     ) -> ::std::os::raw::c_int;
     fn readstat_value_tag(value: readstat_sys::readstat_value_t) -> ::std::os::raw::c_char;
+    fn readstat_value_is_defined_missing(
+        variable: *mut readstat_variable_t,
+        value: readstat_sys::readstat_value_t,
+    ) -> ::std::os::raw::c_int;
 }
 
 pub(crate) unsafe extern "C" fn on_error_cb(message: *const c_char, ctx: *mut c_void) {
@@ -178,6 +233,12 @@ pub(crate) unsafe extern "C" fn on_metadata_cb(
             Some(label.trim().to_string())
         };
     }
+
+    let row_count = readstat_get_row_count(metadata);
+    if row_count > 0 {
+        rctx.row_capacity = Some(row_count as usize);
+    }
+
     HANDLER_OK
 }
 
@@ -220,6 +281,8 @@ pub(crate) unsafe extern "C" fn on_variable_cb(
         }
     }
 
+    let row_cap = rctx.row_capacity.unwrap_or(0);
+
     // Trim label & format strings if present
     let label = {
         let p = readstat_variable_get_label(var);
@@ -324,7 +387,7 @@ pub(crate) unsafe extern "C" fn on_variable_cb(
             label_set,
             fmt,
             user_missing,
-            sb: Some(StringBuilder::new()),
+            sb: Some(StringBuilder::with_capacity(row_cap, row_cap * 8)),
             fb: None,
         },
         ColKind::F64 => ColBuilders {
@@ -335,7 +398,7 @@ pub(crate) unsafe extern "C" fn on_variable_cb(
             fmt,
             user_missing,
             sb: None,
-            fb: Some(Float64Builder::new()),
+            fb: Some(Float64Builder::with_capacity(row_cap)),
         },
     };
 
@@ -367,6 +430,14 @@ pub(crate) unsafe extern "C" fn on_value_cb(
         }
     }
 
+    // Count off row advancement, not a specific column: if the first file
+    // column happens to be in cols_skip, its callback returns early below
+    // and an `idx == 0` check would never see this row at all.
+    if rctx.last_emitted_row != Some(row_us) {
+        rctx.last_emitted_row = Some(row_us);
+        rctx.n_rows_emitted += 1;
+    }
+
     // --- Trim here too so lookups match the map created in on_variable_cb
     let name = {
         let p = readstat_variable_get_name(var);
@@ -399,6 +470,8 @@ pub(crate) unsafe extern "C" fn on_value_cb(
         col.push_missing();
     } else if readstat_value_is_system_missing(value) != 0 {
         col.push_missing();
+    } else if !rctx.user_na && unsafe { readstat_value_is_defined_missing(var, value) } != 0 {
+        col.push_missing();
     } else {
         let vt = rs_value_type(value);
         if vt == T_STRING || vt == T_STRING_REF {
@@ -415,9 +488,6 @@ pub(crate) unsafe extern "C" fn on_value_cb(
         }
     }
 
-    if idx == 0 {
-        rctx.n_rows_emitted += 1;
-    }
     HANDLER_OK
 }
 
@@ -529,13 +599,37 @@ pub(crate) fn finalize_to_ipc(mut ctx: ParseCtx) -> Result<(Vec<u8>, MetaOut)> {
                 });
             }
             ColKind::F64 => {
-                let arr = Arc::new(
-                    col.fb
-                        .take()
-                        .ok_or_else(|| anyhow!("float builder missing"))?
-                        .finish(),
-                ) as ArrayRef;
-                let mut field = Field::new(&col.name, DataType::Float64, true);
+                let f64_arr = col
+                    .fb
+                    .take()
+                    .ok_or_else(|| anyhow!("float builder missing"))?
+                    .finish();
+
+                let mapping = if ctx.apply_value_labels {
+                    col.label_set.as_ref().and_then(|set| ctx.label_sets.get(set))
+                } else {
+                    None
+                };
+
+                let (arr, dtype, kind) = if let Some(mapping) = mapping {
+                    let mut builder = StringDictionaryBuilder::<Int32Type>::new();
+                    for i in 0..f64_arr.len() {
+                        if f64_arr.is_null(i) {
+                            builder.append_null();
+                        } else {
+                            let code = format!("{}", f64_arr.value(i));
+                            let label = mapping.get(&code).cloned().unwrap_or(code);
+                            builder.append_value(&label);
+                        }
+                    }
+                    let dict = builder.finish();
+                    let dtype = dict.data_type().clone();
+                    (Arc::new(dict) as ArrayRef, dtype, "double_labeled")
+                } else {
+                    (Arc::new(f64_arr) as ArrayRef, DataType::Float64, "double")
+                };
+
+                let mut field = Field::new(&col.name, dtype, true);
                 if !fmeta.is_empty() {
                     field = field.with_metadata(fmeta);
                 }
@@ -546,7 +640,7 @@ pub(crate) fn finalize_to_ipc(mut ctx: ParseCtx) -> Result<(Vec<u8>, MetaOut)> {
                     label: col.label,
                     label_set: col.label_set,
                     fmt: col.fmt,
-                    kind: "double".into(),
+                    kind: kind.into(),
                     user_missing: col.user_missing,
                 });
             }
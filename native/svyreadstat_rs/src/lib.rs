@@ -1,6 +1,7 @@
 // native/svyreadstat_rs/src/lib.rs
 mod core;
 mod sas_read;
+mod sas_write;
 mod spss_read;
 mod spss_write;
 mod stata_read;
@@ -14,11 +15,18 @@ use pyo3::prelude::*;
 fn svyreadstat_rs(m: &Bound<PyModule>) -> PyResult<()> {
     // SAS functions
     m.add_function(wrap_pyfunction!(sas_read::df_parse_sas_file, m)?)?;
+    m.add_function(wrap_pyfunction!(sas_write::df_write_sas_file, m)?)?;
 
     // SPSS functions
     m.add_function(wrap_pyfunction!(spss_read::df_parse_sav_file, m)?)?;
+    m.add_function(wrap_pyfunction!(spss_read::df_parse_sav_file_chunked, m)?)?;
     m.add_function(wrap_pyfunction!(spss_read::df_parse_por_file, m)?)?;
     m.add_function(wrap_pyfunction!(spss_write::df_write_sav_file, m)?)?;
+    m.add_function(wrap_pyfunction!(spss_write::df_write_sav_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(spss_write::df_write_sav_file_chunked, m)?)?;
+    m.add_function(wrap_pyfunction!(spss_write::df_write_por_file, m)?)?;
+    m.add_function(wrap_pyfunction!(spss_write::df_write_por_bytes, m)?)?;
+    m.add_class::<spss_read::SavChunkIter>()?;
 
     // Stata functions
     m.add_function(wrap_pyfunction!(stata_read::df_parse_dta_file, m)?)?;
@@ -26,6 +34,7 @@ fn svyreadstat_rs(m: &Bound<PyModule>) -> PyResult<()> {
 
     // XPT functions
     m.add_function(wrap_pyfunction!(xpt_read::df_parse_xpt_file, m)?)?;
+    m.add_function(wrap_pyfunction!(xpt_read::df_parse_xpt_bytes, m)?)?;
     m.add_function(wrap_pyfunction!(xpt_write::df_write_xpt_file, m)?)?;
 
     Ok(())
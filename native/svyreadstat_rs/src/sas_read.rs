@@ -34,6 +34,7 @@ fn parse_sas_impl(
     rows_skip: usize,
     n_max: Option<usize>,
     cols_skip: Option<Vec<String>>,
+    apply_value_labels: bool,
 ) -> Result<(Vec<u8>, crate::core::MetaOut)> {
     // Pre-calculate skip set for O(1) lookup
     let cols_skip_set = cols_skip.map(|v| {
@@ -52,13 +53,16 @@ fn parse_sas_impl(
         n_max,
         n_rows_seen: 0,
         n_rows_emitted: 0,
+        last_emitted_row: None,
         label_sets: HashMap::with_capacity(64), // Pre-allocate for value labels
         file_label: None,
         last_err: None,
         tagged: HashMap::new(), // SAS doesn't use tagged missing
         notes: Vec::with_capacity(4),
         detect_tagged: false, // SAS: no tagged-missing semantics like Stata
+        user_na: false,       // SAS special missing values aren't modeled here
         row_capacity: None,   // Will be filled by on_metadata_cb
+        apply_value_labels,
     };
 
     // Step 1: Parse catalog file if provided (for value labels)
@@ -150,7 +154,8 @@ fn parse_sas_impl(
     _catalog_encoding=None,
     cols_skip=None,
     n_max=None,
-    rows_skip=0
+    rows_skip=0,
+    apply_value_labels=false
 ))]
 pub fn df_parse_sas_file<'py>(
     py: Python<'py>,
@@ -161,10 +166,19 @@ pub fn df_parse_sas_file<'py>(
     cols_skip: Option<Vec<String>>,
     n_max: Option<usize>,
     rows_skip: usize,
+    apply_value_labels: bool,
 ) -> PyResult<(PyObject, String)> {
     // Release GIL during parsing for better Python concurrency
-    let result =
-        py.allow_threads(|| parse_sas_impl(data_path, catalog_path, rows_skip, n_max, cols_skip));
+    let result = py.allow_threads(|| {
+        parse_sas_impl(
+            data_path,
+            catalog_path,
+            rows_skip,
+            n_max,
+            cols_skip,
+            apply_value_labels,
+        )
+    });
 
     let (ipc, meta) =
         result.map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
@@ -184,7 +198,7 @@ mod tests {
 
     #[test]
     fn test_parse_sas_validates_path() {
-        let result = parse_sas_impl("nonexistent.sas7bdat", None, 0, None, None);
+        let result = parse_sas_impl("nonexistent.sas7bdat", None, 0, None, None, false);
         assert!(result.is_err());
     }
 
@@ -192,14 +206,14 @@ mod tests {
     fn test_parse_sas_handles_skip_params() {
         // Test that skip parameters are properly configured
         let cols_skip = Some(vec!["var1".to_string(), "var2".to_string()]);
-        let result = parse_sas_impl("test.sas7bdat", None, 10, Some(50), cols_skip);
+        let result = parse_sas_impl("test.sas7bdat", None, 10, Some(50), cols_skip, false);
         // Will fail on nonexistent file, but tests parameter handling
         assert!(result.is_err());
     }
 
     #[test]
     fn test_parse_sas_with_catalog() {
-        let result = parse_sas_impl("test.sas7bdat", Some("test.sas7bcat"), 0, None, None);
+        let result = parse_sas_impl("test.sas7bdat", Some("test.sas7bcat"), 0, None, None, false);
         // Will fail on nonexistent files, but tests catalog parameter
         assert!(result.is_err());
     }
@@ -0,0 +1,332 @@
+// native/svyreadstat_rs/src/spss_read.rs
+use encoding_rs::Encoding;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::raw::c_void;
+
+use readstat_sys::{
+    readstat_error_e_READSTAT_ERROR_USER_ABORT as RS_USER_ABORT,
+    readstat_error_e_READSTAT_OK as RS_OK, readstat_parse_por, readstat_parse_sav,
+    readstat_parser_free, readstat_parser_init, readstat_parser_t, readstat_set_error_handler,
+    readstat_set_file_character_encoding, readstat_set_metadata_handler, readstat_set_value_handler,
+    readstat_set_value_label_handler, readstat_set_variable_handler,
+};
+
+use crate::core::{
+    finalize_to_ipc, on_error_cb, on_metadata_cb, on_value_cb, on_value_label_cb, on_variable_cb,
+    ParseCtx,
+};
+
+/// Validate and apply a caller-supplied character-encoding override so string
+/// columns and value labels get transcoded correctly even when the charset
+/// declared in the file's own header is wrong (common for legacy surveys
+/// exported from localized SPSS installs). The label is resolved through
+/// `encoding_rs` first, the same validation the writer side already does in
+/// `spss_write::prepare_write_args`, so unknown names fail fast with a
+/// `PyValueError` instead of deep inside the readstat C callbacks.
+unsafe fn apply_encoding_override(
+    parser: *mut readstat_parser_t,
+    encoding: Option<&str>,
+) -> PyResult<()> {
+    let Some(label) = encoding else {
+        return Ok(());
+    };
+    let enc = Encoding::for_label(label.as_bytes()).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!("Unknown encoding label: {label}"))
+    })?;
+    let cname = CString::new(enc.name()).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid encoding name: {e}"))
+    })?;
+    let rc = readstat_set_file_character_encoding(parser, cname.as_ptr());
+    if rc != RS_OK {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Failed to set character encoding '{label}': rc={rc}"
+        )));
+    }
+    Ok(())
+}
+
+/// Run a single `readstat_parse_sav` pass over `[rows_skip, rows_skip + n_max)`
+/// and return the populated `ParseCtx`, without finalizing it to IPC. Shared
+/// by `df_parse_sav_file` (one full pass, `rows_skip=0`/`n_max=None`) and
+/// `SavChunkIter` (one pass per chunk, advancing `rows_skip` each call) so
+/// the two only differ in how they slice up the row range.
+fn parse_sav_range(
+    path: &str,
+    encoding: Option<&str>,
+    user_na: bool,
+    cols_skip_map: Option<HashMap<String, ()>>,
+    rows_skip: usize,
+    n_max: Option<usize>,
+    apply_value_labels: bool,
+) -> PyResult<ParseCtx> {
+    let mut ctx = ParseCtx {
+        cols: Vec::new(),
+        name_to_idx: HashMap::new(),
+        cols_skip: cols_skip_map,
+        rows_skip,
+        n_max,
+        n_rows_seen: 0,
+        n_rows_emitted: 0,
+        last_emitted_row: None,
+        label_sets: HashMap::new(),
+        file_label: None,
+        last_err: None,
+        tagged: HashMap::new(),
+        notes: Vec::new(),
+        detect_tagged: false, // SPSS has no Stata-style tagged missings
+        user_na,
+        row_capacity: None, // filled in by on_metadata_cb
+        apply_value_labels,
+    };
+
+    unsafe {
+        let p = readstat_parser_init();
+        if p.is_null() {
+            return Err(pyo3::exceptions::PyRuntimeError::new_err(
+                "readstat_parser_init() failed",
+            ));
+        }
+
+        if let Err(e) = apply_encoding_override(p, encoding) {
+            readstat_parser_free(p);
+            return Err(e);
+        }
+
+        readstat_set_error_handler(p, Some(on_error_cb));
+        readstat_set_metadata_handler(p, Some(on_metadata_cb));
+        readstat_set_variable_handler(p, Some(on_variable_cb));
+        readstat_set_value_handler(p, Some(on_value_cb));
+        readstat_set_value_label_handler(p, Some(on_value_label_cb));
+
+        let cpath = CString::new(path)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid path: {e}")))?;
+        let rc = readstat_parse_sav(p, cpath.as_ptr(), &mut ctx as *mut _ as *mut c_void);
+        readstat_parser_free(p);
+
+        let early_ok = ctx
+            .n_max
+            .map(|nm| ctx.n_rows_emitted >= nm)
+            .unwrap_or(false);
+        if rc != RS_OK && !early_ok && rc != RS_USER_ABORT {
+            let msg = ctx.last_err.take().unwrap_or_else(|| format!("rc={rc}"));
+            return Err(pyo3::exceptions::PyRuntimeError::new_err(format!(
+                "Failed to parse SAV: {msg}"
+            )));
+        }
+    }
+
+    Ok(ctx)
+}
+
+/// Parse SPSS .sav file
+#[pyfunction]
+#[pyo3(signature = (path, encoding=None, user_na=false, cols_skip=None, n_max=None, rows_skip=0, apply_value_labels=false))]
+pub fn df_parse_sav_file(
+    py: Python<'_>,
+    path: &str,
+    encoding: Option<&str>, // override the charset declared in the file header
+    user_na: bool,          // keep user-defined missing values instead of nulling them
+    cols_skip: Option<Vec<String>>,
+    n_max: Option<usize>,
+    rows_skip: usize,
+    apply_value_labels: bool,
+) -> PyResult<(PyObject, String)> {
+    let cols_skip_map = cols_skip.map(|v| v.into_iter().map(|k| (k, ())).collect());
+    let ctx = parse_sav_range(
+        path,
+        encoding,
+        user_na,
+        cols_skip_map,
+        rows_skip,
+        n_max,
+        apply_value_labels,
+    )?;
+
+    let (ipc, meta) = finalize_to_ipc(ctx)
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("finalize_to_ipc: {e}")))?;
+    let meta_json = serde_json::to_string(&meta).map_err(|e| {
+        pyo3::exceptions::PyRuntimeError::new_err(format!("JSON serialize metadata: {e}"))
+    })?;
+    let pybytes = PyBytes::new_bound(py, &ipc).into_py(py);
+    Ok((pybytes, meta_json))
+}
+
+/// Iterator returned by `df_parse_sav_file_chunked`: each `__next__` runs one
+/// bounded `parse_sav_range` pass (`rows_skip` advancing by the previous
+/// chunk's row count, `n_max=batch_rows`) and finalizes just that slice to
+/// its own Arrow IPC batch, so peak memory is one chunk rather than the
+/// whole file. Note this re-scans the file from the start on every chunk
+/// (readstat has no pause/resume hook, only the `rows_skip`/`n_max` bounds
+/// already used by `df_parse_sav_file`), so it trades some extra read I/O
+/// for a hard cap on memory; it stops as soon as a pass emits fewer rows
+/// than `batch_rows`.
+#[pyclass]
+pub struct SavChunkIter {
+    path: String,
+    encoding: Option<String>,
+    user_na: bool,
+    cols_skip: Option<Vec<String>>,
+    batch_rows: usize,
+    next_rows_skip: usize,
+    done: bool,
+    apply_value_labels: bool,
+}
+
+#[pymethods]
+impl SavChunkIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<Option<(PyObject, String)>> {
+        if slf.done {
+            return Ok(None);
+        }
+
+        let cols_skip_map = slf
+            .cols_skip
+            .clone()
+            .map(|v| v.into_iter().map(|k| (k, ())).collect());
+        let ctx = parse_sav_range(
+            &slf.path,
+            slf.encoding.as_deref(),
+            slf.user_na,
+            cols_skip_map,
+            slf.next_rows_skip,
+            Some(slf.batch_rows),
+            slf.apply_value_labels,
+        )?;
+
+        let n_emitted = ctx.n_rows_emitted;
+        if n_emitted == 0 {
+            slf.done = true;
+            return Ok(None);
+        }
+        if n_emitted < slf.batch_rows {
+            slf.done = true;
+        }
+        slf.next_rows_skip += n_emitted;
+
+        let (ipc, meta) = finalize_to_ipc(ctx)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("finalize_to_ipc: {e}")))?;
+        let meta_json = serde_json::to_string(&meta).map_err(|e| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!("JSON serialize metadata: {e}"))
+        })?;
+        let pybytes = PyBytes::new_bound(py, &ipc).into_py(py);
+        Ok(Some((pybytes, meta_json)))
+    }
+}
+
+/// Streaming variant of `df_parse_sav_file`: returns a Python iterator that
+/// yields `(ipc_bytes, meta_json)` one `batch_rows`-sized chunk at a time,
+/// so a multi-gigabyte `.sav` can be folded into a Polars/Arrow frame
+/// incrementally instead of materializing the whole table at once.
+#[pyfunction]
+#[pyo3(signature = (path, batch_rows, encoding=None, user_na=false, cols_skip=None, apply_value_labels=false))]
+pub fn df_parse_sav_file_chunked(
+    path: &str,
+    batch_rows: usize,
+    encoding: Option<&str>,
+    user_na: bool,
+    cols_skip: Option<Vec<String>>,
+    apply_value_labels: bool,
+) -> PyResult<SavChunkIter> {
+    if batch_rows == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "batch_rows must be greater than zero",
+        ));
+    }
+    Ok(SavChunkIter {
+        path: path.to_string(),
+        encoding: encoding.map(|s| s.to_string()),
+        user_na,
+        cols_skip,
+        batch_rows,
+        next_rows_skip: 0,
+        done: false,
+        apply_value_labels,
+    })
+}
+
+/// Parse SPSS portable (.por) file
+#[pyfunction]
+#[pyo3(signature = (path, encoding=None, user_na=false, cols_skip=None, n_max=None, rows_skip=0, apply_value_labels=false))]
+pub fn df_parse_por_file(
+    py: Python<'_>,
+    path: &str,
+    encoding: Option<&str>, // override the charset declared in the file header
+    user_na: bool,          // keep user-defined missing values instead of nulling them
+    cols_skip: Option<Vec<String>>,
+    n_max: Option<usize>,
+    rows_skip: usize,
+    apply_value_labels: bool,
+) -> PyResult<(PyObject, String)> {
+    let cols_skip_map = cols_skip.map(|v| v.into_iter().map(|k| (k, ())).collect());
+
+    let mut ctx = ParseCtx {
+        cols: Vec::new(),
+        name_to_idx: HashMap::new(),
+        cols_skip: cols_skip_map,
+        rows_skip,
+        n_max,
+        n_rows_seen: 0,
+        n_rows_emitted: 0,
+        last_emitted_row: None,
+        label_sets: HashMap::new(),
+        file_label: None,
+        last_err: None,
+        tagged: HashMap::new(),
+        notes: Vec::new(),
+        detect_tagged: false,
+        user_na,
+        row_capacity: None, // filled in by on_metadata_cb
+        apply_value_labels,
+    };
+
+    unsafe {
+        let p = readstat_parser_init();
+        if p.is_null() {
+            return Err(pyo3::exceptions::PyRuntimeError::new_err(
+                "readstat_parser_init() failed",
+            ));
+        }
+
+        if let Err(e) = apply_encoding_override(p, encoding) {
+            readstat_parser_free(p);
+            return Err(e);
+        }
+
+        readstat_set_error_handler(p, Some(on_error_cb));
+        readstat_set_metadata_handler(p, Some(on_metadata_cb));
+        readstat_set_variable_handler(p, Some(on_variable_cb));
+        readstat_set_value_handler(p, Some(on_value_cb));
+        readstat_set_value_label_handler(p, Some(on_value_label_cb));
+
+        let cpath = CString::new(path)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid path: {e}")))?;
+        let rc = readstat_parse_por(p, cpath.as_ptr(), &mut ctx as *mut _ as *mut c_void);
+        readstat_parser_free(p);
+
+        let early_ok = ctx
+            .n_max
+            .map(|nm| ctx.n_rows_emitted >= nm)
+            .unwrap_or(false);
+        if rc != RS_OK && !early_ok && rc != RS_USER_ABORT {
+            let msg = ctx.last_err.take().unwrap_or_else(|| format!("rc={rc}"));
+            return Err(pyo3::exceptions::PyRuntimeError::new_err(format!(
+                "Failed to parse POR: {msg}"
+            )));
+        }
+    }
+
+    let (ipc, meta) = finalize_to_ipc(ctx)
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("finalize_to_ipc: {e}")))?;
+    let meta_json = serde_json::to_string(&meta).map_err(|e| {
+        pyo3::exceptions::PyRuntimeError::new_err(format!("JSON serialize metadata: {e}"))
+    })?;
+    let pybytes = PyBytes::new_bound(py, &ipc).into_py(py);
+    Ok((pybytes, meta_json))
+}
@@ -1,12 +1,13 @@
 // native/svyreadstat_rs/src/spss_write.rs
 use anyhow::{anyhow, Result};
+use encoding_rs::Encoding;
 use pyo3::prelude::*;
 use pyo3::types::PyBytes;
 
 use std::collections::HashMap;
 use std::ffi::CString;
 use std::fs::File;
-use std::io::{Cursor, Write as IoWrite};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write as IoWrite};
 use std::os::raw::c_void;
 use std::path::Path;
 
@@ -22,26 +23,50 @@ use arrow::datatypes::{
     DataType, Int16Type, Int32Type, Int64Type, Int8Type, TimeUnit, UInt16Type, UInt32Type,
     UInt64Type, UInt8Type,
 };
-use arrow::ipc::reader::{FileReader, StreamReader};
-use arrow::record_batch::RecordBatch;
+use arrow::ipc::reader::{FileReader, IpcReadOptions, StreamReader};
+use arrow::record_batch::RecordBatchReader;
 
 use readstat_sys::{
-    readstat_add_label_set, readstat_add_variable, readstat_begin_row, readstat_begin_writing_sav,
+    readstat_add_label_set, readstat_add_variable, readstat_alignment_e_READSTAT_ALIGNMENT_CENTER as ALIGN_CENTER,
+    readstat_alignment_e_READSTAT_ALIGNMENT_LEFT as ALIGN_LEFT,
+    readstat_alignment_e_READSTAT_ALIGNMENT_RIGHT as ALIGN_RIGHT, readstat_begin_row,
+    readstat_begin_writing_por, readstat_begin_writing_sav,
+    readstat_compress_e_READSTAT_COMPRESS_BINARY as COMPRESS_BINARY,
     readstat_compress_e_READSTAT_COMPRESS_NONE as COMPRESS_NONE,
     readstat_compress_e_READSTAT_COMPRESS_ROWS as COMPRESS_ROWS, readstat_end_row,
     readstat_end_writing, readstat_insert_double_value, readstat_insert_missing_value,
     readstat_insert_string_value, readstat_label_double_value, readstat_label_set_t,
-    readstat_label_string_value, readstat_set_data_writer,
+    readstat_label_string_value,
+    readstat_measure_e_READSTAT_MEASURE_NOMINAL as MEASURE_NOMINAL,
+    readstat_measure_e_READSTAT_MEASURE_ORDINAL as MEASURE_ORDINAL,
+    readstat_measure_e_READSTAT_MEASURE_SCALE as MEASURE_SCALE, readstat_set_data_writer,
     readstat_type_e_READSTAT_TYPE_DOUBLE as T_DOUBLE,
     readstat_type_e_READSTAT_TYPE_STRING as T_STRING, readstat_variable_add_missing_double_range,
     readstat_variable_add_missing_double_value, readstat_variable_add_missing_string_value,
+    readstat_variable_set_alignment, readstat_variable_set_display_width,
     readstat_variable_set_format, readstat_variable_set_label, readstat_variable_set_label_set,
-    readstat_variable_t, readstat_writer_free, readstat_writer_init,
-    readstat_writer_set_compression, readstat_writer_set_file_label,
+    readstat_variable_set_measure, readstat_variable_t, readstat_writer_free, readstat_writer_init,
+    readstat_writer_set_compression, readstat_writer_set_file_charset,
+    readstat_writer_set_file_label,
 };
 
-/// ReadStat data sink: write to a std::fs::File
-unsafe extern "C" fn data_writer_cb(
+/// Transcode a UTF-8 string to the target `encoding` (falling back to UTF-8 when
+/// `encoding` is `None`) and wrap the result in a `CString`, the same failure mode
+/// (`CString::new`'s embedded-NUL rejection) as plain UTF-8 output.
+fn to_target_cstring(
+    s: &str,
+    encoding: Option<&'static Encoding>,
+) -> Result<CString, std::ffi::NulError> {
+    match encoding {
+        Some(enc) => CString::new(enc.encode(s).0.into_owned()),
+        None => CString::new(s),
+    }
+}
+
+/// ReadStat data sink: write to any `std::io::Write`, monomorphized per sink
+/// type (a `std::fs::File` for `df_write_sav_file`, a growable `Vec<u8>` for
+/// `df_write_sav_bytes`).
+unsafe extern "C" fn data_writer_cb<W: IoWrite>(
     data: *const std::os::raw::c_void,
     len: usize,
     ctx: *mut c_void,
@@ -49,28 +74,76 @@ unsafe extern "C" fn data_writer_cb(
     if data.is_null() || ctx.is_null() {
         return -1;
     }
-    let file = &mut *(ctx as *mut File);
+    let sink = &mut *(ctx as *mut W);
     let bytes = std::slice::from_raw_parts(data as *const u8, len);
-    match file.write_all(bytes) {
+    match sink.write_all(bytes) {
         Ok(_) => len as isize,
         Err(_) => -1,
     }
 }
 
-fn ipc_to_batches(buf: &[u8]) -> Result<Vec<RecordBatch>> {
-    let mut batches = Vec::new();
+/// Open a streaming reader over Arrow IPC bytes (file or stream framing),
+/// yielding one `RecordBatch` at a time instead of materializing all of them.
+/// Keeps memory bounded to a single batch during the write passes below,
+/// rather than the whole decoded table. Built with `IpcReadOptions` rather
+/// than a bare `None` so LZ4_FRAME/ZSTD-compressed body buffers inflate
+/// transparently too — contingent on `arrow` being built with its
+/// "lz4"/"zstd" features, which callers pulling batches out of this reader
+/// map through `describe_ipc_decode_err` to surface clearly if not.
+fn open_ipc_reader(buf: &[u8]) -> Result<Box<dyn RecordBatchReader + '_>> {
+    let options = IpcReadOptions::default();
     if buf.starts_with(b"ARROW1") {
-        let mut fr = FileReader::try_new(Cursor::new(buf), None)?;
-        for b in fr.by_ref() {
-            batches.push(b?);
-        }
+        Ok(Box::new(FileReader::try_new_with_options(
+            Cursor::new(buf),
+            options,
+        )?))
+    } else {
+        Ok(Box::new(StreamReader::try_new_with_options(
+            Cursor::new(buf),
+            options,
+        )?))
+    }
+}
+
+/// Same framing detection as `open_ipc_reader`, but sourced from a file on
+/// disk instead of an in-memory slice, so the caller never has to hold the
+/// whole serialized IPC payload resident to open a reader over it. Used by
+/// the spill-file path the `_chunked` entry points feed into
+/// `write_spss_minimal`.
+fn open_ipc_reader_from_file(path: &Path) -> Result<Box<dyn RecordBatchReader>> {
+    let mut f = File::open(path)?;
+    let mut magic = [0u8; 6];
+    let n = f.read(&mut magic)?;
+    f.seek(SeekFrom::Start(0))?;
+    let options = IpcReadOptions::default();
+    if n == 6 && &magic == b"ARROW1" {
+        Ok(Box::new(FileReader::try_new_with_options(f, options)?))
     } else {
-        let mut sr = StreamReader::try_new(Cursor::new(buf), None)?;
-        while let Some(res) = sr.next() {
-            batches.push(res?);
+        Ok(Box::new(StreamReader::try_new_with_options(f, options)?))
+    }
+}
+
+/// Where `write_spss_minimal` reads its Arrow IPC input from. `Bytes` is the
+/// plain in-memory case (`df_write_sav_file`/`df_write_sav_bytes`, where the
+/// caller already handed us the whole buffer); `Path` is used by the
+/// `_chunked` entry points, which spill a Python byte-chunk generator to a
+/// temp file first (since the writer needs two independent passes over the
+/// data and a generator can only be drained once) and never hold the whole
+/// payload in memory at once.
+enum IpcSource<'a> {
+    Bytes(&'a [u8]),
+    Path(&'a Path),
+}
+
+impl<'a> IpcSource<'a> {
+    fn open(&self) -> Result<Box<dyn RecordBatchReader + 'a>> {
+        match self {
+            IpcSource::Bytes(buf) => open_ipc_reader(buf),
+            IpcSource::Path(p) => {
+                open_ipc_reader_from_file(p).map(|r| r as Box<dyn RecordBatchReader + 'a>)
+            }
         }
     }
-    Ok(batches)
 }
 
 #[inline]
@@ -249,25 +322,50 @@ struct StringColStats {
     max_len: usize,
 }
 
-fn compute_string_metadata(batches: &[RecordBatch]) -> Vec<Option<StringColStats>> {
-    if batches.is_empty() {
-        return Vec::new();
+/// SPSS system files cap any one string variable (including "very long string"
+/// segments ReadStat stitches together under the hood) at this many bytes.
+const SPSS_MAX_STRING_WIDTH: usize = 32_767;
+
+fn encoded_len(s: &str, encoding: Option<&'static Encoding>) -> usize {
+    match encoding {
+        Some(enc) => enc.encode(s).0.len(),
+        None => s.as_bytes().len(),
     }
-    let ncols = batches[0].schema().fields().len();
-    let mut all_stats: Vec<Option<StringColStats>> = vec![None; ncols];
+}
 
-    for b in batches {
-        for (j, f) in b.schema().fields().iter().enumerate() {
-            let col = b.column(j);
-            let is_str = is_text_dt(f.data_type())
-                || matches!(f.data_type(), &DataType::Dictionary(_, ref v) if is_text_dt(v.as_ref()));
-            if !is_str {
+/// First streaming pass: scan every batch once to collect the per-column
+/// string stats and total row count `write_spss_minimal` needs before it can
+/// declare variables, without holding more than one decoded batch at a time.
+struct ScanResult {
+    str_stats: Vec<Option<StringColStats>>,
+    total_rows: i64,
+    saw_batch: bool,
+}
+
+fn scan_batches(
+    source: &IpcSource,
+    ncols: usize,
+    is_str_col: &[bool],
+    encoding: Option<&'static Encoding>,
+) -> Result<ScanResult> {
+    let mut str_stats: Vec<Option<StringColStats>> = vec![None; ncols];
+    let mut total_rows: i64 = 0;
+    let mut saw_batch = false;
+
+    let reader = source.open()?;
+    for b in reader {
+        let b = b.map_err(crate::core::describe_ipc_decode_err)?;
+        saw_batch = true;
+        total_rows += b.num_rows() as i64;
+        for j in 0..ncols {
+            if !is_str_col[j] {
                 continue;
             }
-            let stats = all_stats[j].get_or_insert(StringColStats::default());
+            let col = b.column(j);
+            let stats = str_stats[j].get_or_insert(StringColStats::default());
             for i in 0..col.len() {
                 if let Some(s) = get_string_value(col.as_ref(), i) {
-                    let blen = s.as_bytes().len();
+                    let blen = encoded_len(s, encoding);
                     if blen > stats.max_len {
                         stats.max_len = blen;
                     }
@@ -275,7 +373,12 @@ fn compute_string_metadata(batches: &[RecordBatch]) -> Vec<Option<StringColStats
             }
         }
     }
-    all_stats
+
+    Ok(ScanResult {
+        str_stats,
+        total_rows,
+        saw_batch,
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -291,50 +394,140 @@ struct ValueLabelsInfo {
     labels: HashMap<String, String>,
 }
 
-fn write_spss_minimal(
-    batches: &[RecordBatch],
-    out_path: &str,
+fn measure_from_label(s: &str) -> Option<u32> {
+    match s {
+        "nominal" => Some(MEASURE_NOMINAL),
+        "ordinal" => Some(MEASURE_ORDINAL),
+        "scale" => Some(MEASURE_SCALE),
+        _ => None,
+    }
+}
+
+fn alignment_from_label(s: &str) -> Option<u32> {
+    match s {
+        "left" => Some(ALIGN_LEFT),
+        "center" | "centre" => Some(ALIGN_CENTER),
+        "right" => Some(ALIGN_RIGHT),
+        _ => None,
+    }
+}
+
+/// Infer a sensible default measurement level from the Arrow type when the
+/// caller didn't supply one: numeric columns default to scale, string and
+/// dictionary (categorical) columns default to nominal.
+fn inferred_measure(dt: &DataType, is_str_col: bool) -> u32 {
+    if is_str_col || matches!(dt, DataType::Dictionary(_, _)) {
+        MEASURE_NOMINAL
+    } else {
+        MEASURE_SCALE
+    }
+}
+
+/// SPSS print/write format string for an explicit `var_formats` label (see
+/// savReaderWriter's date-field writing for the same vocabulary). Width and
+/// decimals are chosen wide enough for the format's calendar/clock fields, or
+/// SPSS refuses to open the file.
+fn temporal_format_for_label(s: &str) -> Option<&'static str> {
+    match s.to_ascii_lowercase().as_str() {
+        "date" => Some("DATE11"),
+        "adate" => Some("ADATE10"),
+        "edate" => Some("EDATE10"),
+        "time" => Some("TIME11.2"),
+        "datetime" => Some("DATETIME20"),
+        _ => None,
+    }
+}
+
+/// Default print/write format inferred from the Arrow type when the caller
+/// didn't request one explicitly via `var_formats`.
+fn default_temporal_format(dt: &DataType) -> Option<&'static str> {
+    match dt {
+        DataType::Date32 | DataType::Date64 => Some("DATE11"),
+        DataType::Timestamp(_, _) => Some("DATETIME20"),
+        DataType::Duration(_) => Some("TIME11.2"),
+        _ => None,
+    }
+}
+
+/// SPSS case-data compression mode. `Zlib` selects the ZSAV container (a
+/// ZHEADER/ZTRAILER-framed sequence of independently zlib-deflated data
+/// blocks) rather than the legacy byte-run scheme; both are produced by the
+/// underlying ReadStat writer once the matching `readstat_compress_e` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    ByteRun,
+    Zlib,
+}
+
+impl Compression {
+    fn parse(s: &str) -> Self {
+        match s {
+            "none" => Compression::None,
+            "zlib" | "zsav" => Compression::Zlib,
+            _ => Compression::ByteRun,
+        }
+    }
+
+    fn readstat_compress(self) -> u32 {
+        match self {
+            Compression::None => COMPRESS_NONE,
+            Compression::ByteRun => COMPRESS_ROWS,
+            Compression::Zlib => COMPRESS_BINARY,
+        }
+    }
+}
+
+fn write_spss_minimal<W: IoWrite>(
+    source: IpcSource,
+    sink: &mut W,
     file_label: Option<&str>,
     compress: &str,
+    encoding: Option<&'static Encoding>,
     var_labels: Option<&HashMap<String, String>>,
     user_missing: Option<&[UserMissingInfo]>,
     value_labels: Option<&[ValueLabelsInfo]>,
+    var_measures: Option<&HashMap<String, String>>,
+    var_display_widths: Option<&HashMap<String, i32>>,
+    var_alignments: Option<&HashMap<String, String>>,
+    var_formats: Option<&HashMap<String, String>>,
 ) -> Result<()> {
-    if batches.is_empty() {
-        let _ = File::create(out_path)?;
+    // Peek the schema (cheap: just the IPC schema message) to size the
+    // per-column bookkeeping before the first streaming pass.
+    let schema = source.open()?.schema();
+    let ncols = schema.fields().len();
+    let mut is_str_col: Vec<bool> = vec![false; ncols];
+    for (j, f) in schema.fields().iter().enumerate() {
+        is_str_col[j] = is_text_dt(f.data_type())
+            || matches!(f.data_type(), &DataType::Dictionary(_, ref v) if is_text_dt(v.as_ref()));
+    }
+
+    // Pass 1: scan all batches (one at a time) for string widths and row count.
+    let scan = scan_batches(&source, ncols, &is_str_col, encoding)?;
+    if !scan.saw_batch {
         return Ok(());
     }
+    let str_stats = scan.str_stats;
 
     let writer = unsafe { readstat_writer_init() };
     if writer.is_null() {
         return Err(anyhow!("readstat_writer_init() failed"));
     }
 
-    let compress_type = match compress {
-        "none" => COMPRESS_NONE,
-        "byte" => COMPRESS_ROWS,
-        _ => COMPRESS_ROWS,
-    };
+    let compression = Compression::parse(compress);
     unsafe {
-        readstat_writer_set_compression(writer, compress_type);
-        readstat_set_data_writer(writer, Some(data_writer_cb));
+        readstat_writer_set_compression(writer, compression.readstat_compress());
+        readstat_set_data_writer(writer, Some(data_writer_cb::<W>));
     }
 
     if let Some(lbl) = file_label {
-        let c = CString::new(lbl)?;
+        let c = to_target_cstring(lbl, encoding)?;
         unsafe { readstat_writer_set_file_label(writer, c.as_ptr()) };
     }
 
-    let schema = batches[0].schema();
-    let ncols = schema.fields().len();
-
-    let str_stats = compute_string_metadata(batches);
-    let mut is_str_col: Vec<bool> = vec![false; ncols];
-
-    for j in 0..ncols {
-        let dt = batches[0].column(j).data_type();
-        is_str_col[j] = is_text_dt(dt)
-            || matches!(dt, DataType::Dictionary(_, ref v) if is_text_dt(v.as_ref()));
+    let charset_name = encoding.map(|enc| enc.name()).unwrap_or("UTF-8");
+    if let Ok(c) = CString::new(charset_name) {
+        unsafe { readstat_writer_set_file_charset(writer, c.as_ptr()) };
     }
 
     let mut rvars: Vec<*const readstat_variable_t> = Vec::with_capacity(ncols);
@@ -343,14 +536,28 @@ fn write_spss_minimal(
 
     // Define variables
     for (j, field) in schema.fields().iter().enumerate() {
-        let dt = batches[0].column(j).data_type();
+        let dt = field.data_type();
         let mut typ = T_DOUBLE;
         let mut width: usize = 0;
 
         if is_str_col[j] {
             let stats = str_stats[j].unwrap_or(StringColStats { max_len: 1 });
+            if stats.max_len > SPSS_MAX_STRING_WIDTH {
+                unsafe { readstat_writer_free(writer) };
+                return Err(anyhow!(
+                    "Column '{}' has values up to {} bytes, which exceeds SPSS's \
+                     maximum string width of {} bytes (very long strings are segmented \
+                     by ReadStat, but cannot exceed this limit)",
+                    field.name(),
+                    stats.max_len,
+                    SPSS_MAX_STRING_WIDTH
+                ));
+            }
             typ = T_STRING;
-            width = std::cmp::max(1, std::cmp::min(2000, stats.max_len));
+            // Widths beyond 255 are "very long strings"; ReadStat transparently
+            // segments them into 255-byte chunks on write and reassembles them
+            // on read, so we just pass the true byte width through.
+            width = std::cmp::max(1, stats.max_len);
         }
 
         let cname = CString::new(field.name().as_str())?;
@@ -363,13 +570,13 @@ fn write_spss_minimal(
             ));
         }
 
-        // SPSS display format for temporal columns
-        let want_fmt = match dt {
-            DataType::Date32 | DataType::Date64 => Some("DATE10"),
-            DataType::Timestamp(_, _) => Some("DATETIME20"),
-            DataType::Duration(_) => Some("TIME11.2"),
-            _ => None,
-        };
+        // SPSS print/write format: an explicit `var_formats` entry (date /
+        // adate / edate / time / datetime) wins, else infer from the Arrow
+        // temporal type.
+        let want_fmt = var_formats
+            .and_then(|m| m.get(field.name()))
+            .and_then(|s| temporal_format_for_label(s))
+            .or_else(|| default_temporal_format(dt));
         if let Some(fmt) = want_fmt {
             if let Ok(cfmt) = CString::new(fmt) {
                 unsafe { readstat_variable_set_format(var, cfmt.as_ptr()) };
@@ -380,13 +587,33 @@ fn write_spss_minimal(
         if let Some(map) = var_labels {
             if let Some(lbl) = map.get(field.name()) {
                 if !lbl.is_empty() {
-                    if let Ok(c) = CString::new(lbl.as_str()) {
+                    if let Ok(c) = to_target_cstring(lbl, encoding) {
                         unsafe { readstat_variable_set_label(var, c.as_ptr()) };
                     }
                 }
             }
         }
 
+        // Measurement level: explicit override, else infer from Arrow type
+        let measure = var_measures
+            .and_then(|m| m.get(field.name()))
+            .and_then(|s| measure_from_label(s))
+            .unwrap_or_else(|| inferred_measure(dt, is_str_col[j]));
+        unsafe { readstat_variable_set_measure(var, measure) };
+
+        // Column display width (Variable View width)
+        if let Some(w) = var_display_widths.and_then(|m| m.get(field.name())) {
+            unsafe { readstat_variable_set_display_width(var, *w) };
+        }
+
+        // Alignment
+        if let Some(align) = var_alignments
+            .and_then(|m| m.get(field.name()))
+            .and_then(|s| alignment_from_label(s))
+        {
+            unsafe { readstat_variable_set_alignment(var, align) };
+        }
+
         // User-defined missing values
         if let Some(user_miss) = user_missing {
             for um in user_miss {
@@ -438,10 +665,10 @@ fn write_spss_minimal(
                         let mut c_strings = Vec::new();
 
                         for (value, label) in &vl.labels {
-                            if let Ok(c_label) = CString::new(label.as_str()) {
+                            if let Ok(c_label) = to_target_cstring(label, encoding) {
                                 if is_str_col[j] {
                                     // String value labels
-                                    if let Ok(c_val) = CString::new(value.as_str()) {
+                                    if let Ok(c_val) = to_target_cstring(value, encoding) {
                                         unsafe {
                                             readstat_label_string_value(
                                                 label_set,
@@ -481,14 +708,12 @@ fn write_spss_minimal(
         rvars.push(var);
     }
 
-    // Open output and begin writing
-    let mut outfile = File::create(Path::new(out_path))?;
-    let total_rows: i64 = batches.iter().map(|b| b.num_rows() as i64).sum();
+    // Begin writing into the caller-provided sink
     unsafe {
         let rc = readstat_begin_writing_sav(
             writer,
-            &mut outfile as *mut File as *mut c_void,
-            total_rows.try_into().expect("row count overflow"),
+            sink as *mut W as *mut c_void,
+            scan.total_rows.try_into().expect("row count overflow"),
         );
         if rc != 0 {
             readstat_writer_free(writer);
@@ -496,8 +721,10 @@ fn write_spss_minimal(
         }
     }
 
-    // Write rows
-    for b in batches {
+    // Pass 2: stream the rows, one decoded batch at a time.
+    let reader = source.open()?;
+    for b in reader {
+        let b = b.map_err(crate::core::describe_ipc_decode_err)?;
         for i in 0..b.num_rows() {
             unsafe {
                 let rc = readstat_begin_row(writer);
@@ -511,7 +738,7 @@ fn write_spss_minimal(
                 if is_str_col[j] {
                     if let Some(s) = get_string_value(arr.as_ref(), i) {
                         unsafe {
-                            match CString::new(s) {
+                            match to_target_cstring(s, encoding) {
                                 Ok(cs) => {
                                     let rc =
                                         readstat_insert_string_value(writer, rvars[j], cs.as_ptr());
@@ -591,72 +818,662 @@ fn write_spss_minimal(
     Ok(())
 }
 
+/// Same two-pass streaming strategy as `write_spss_minimal`, but targeting
+/// readstat's POR (SPSS portable) writer instead of SAV. POR is a plain-text
+/// interchange format with no compression and no Variable-View-era metadata
+/// (measurement level, display width, alignment, `$@Role`, `$MRSETS`), so
+/// those channels simply aren't accepted here rather than being silently
+/// validated-and-dropped like they are for SAV.
+fn write_por_minimal<W: IoWrite>(
+    ipc_bytes: &[u8],
+    sink: &mut W,
+    file_label: Option<&str>,
+    encoding: Option<&'static Encoding>,
+    var_labels: Option<&HashMap<String, String>>,
+    user_missing: Option<&[UserMissingInfo]>,
+    value_labels: Option<&[ValueLabelsInfo]>,
+    var_formats: Option<&HashMap<String, String>>,
+) -> Result<()> {
+    let schema = open_ipc_reader(ipc_bytes)?.schema();
+    let ncols = schema.fields().len();
+    let mut is_str_col: Vec<bool> = vec![false; ncols];
+    for (j, f) in schema.fields().iter().enumerate() {
+        is_str_col[j] = is_text_dt(f.data_type())
+            || matches!(f.data_type(), &DataType::Dictionary(_, ref v) if is_text_dt(v.as_ref()));
+    }
+
+    let scan = scan_batches(&IpcSource::Bytes(ipc_bytes), ncols, &is_str_col, encoding)?;
+    if !scan.saw_batch {
+        return Ok(());
+    }
+    let str_stats = scan.str_stats;
+
+    let writer = unsafe { readstat_writer_init() };
+    if writer.is_null() {
+        return Err(anyhow!("readstat_writer_init() failed"));
+    }
+
+    unsafe { readstat_set_data_writer(writer, Some(data_writer_cb::<W>)) };
+
+    if let Some(lbl) = file_label {
+        let c = to_target_cstring(lbl, encoding)?;
+        unsafe { readstat_writer_set_file_label(writer, c.as_ptr()) };
+    }
+
+    let charset_name = encoding.map(|enc| enc.name()).unwrap_or("UTF-8");
+    if let Ok(c) = CString::new(charset_name) {
+        unsafe { readstat_writer_set_file_charset(writer, c.as_ptr()) };
+    }
+
+    let mut rvars: Vec<*const readstat_variable_t> = Vec::with_capacity(ncols);
+    let mut _keep_names: Vec<CString> = Vec::with_capacity(ncols);
+    let mut _keep_label_sets: Vec<(*const readstat_label_set_t, Vec<CString>)> = Vec::new();
+
+    for (j, field) in schema.fields().iter().enumerate() {
+        let dt = field.data_type();
+        let mut typ = T_DOUBLE;
+        let mut width: usize = 0;
+
+        if is_str_col[j] {
+            let stats = str_stats[j].unwrap_or(StringColStats { max_len: 1 });
+            if stats.max_len > SPSS_MAX_STRING_WIDTH {
+                unsafe { readstat_writer_free(writer) };
+                return Err(anyhow!(
+                    "Column '{}' has values up to {} bytes, which exceeds SPSS's \
+                     maximum string width of {} bytes",
+                    field.name(),
+                    stats.max_len,
+                    SPSS_MAX_STRING_WIDTH
+                ));
+            }
+            typ = T_STRING;
+            width = std::cmp::max(1, stats.max_len);
+        }
+
+        let cname = CString::new(field.name().as_str())?;
+        let var = unsafe { readstat_add_variable(writer, cname.as_ptr(), typ, width as _) };
+        if var.is_null() {
+            unsafe { readstat_writer_free(writer) };
+            return Err(anyhow!(
+                "readstat_add_variable failed for '{}'",
+                field.name()
+            ));
+        }
+
+        let want_fmt = var_formats
+            .and_then(|m| m.get(field.name()))
+            .and_then(|s| temporal_format_for_label(s))
+            .or_else(|| default_temporal_format(dt));
+        if let Some(fmt) = want_fmt {
+            if let Ok(cfmt) = CString::new(fmt) {
+                unsafe { readstat_variable_set_format(var, cfmt.as_ptr()) };
+            }
+        }
+
+        if let Some(map) = var_labels {
+            if let Some(lbl) = map.get(field.name()) {
+                if !lbl.is_empty() {
+                    if let Ok(c) = to_target_cstring(lbl, encoding) {
+                        unsafe { readstat_variable_set_label(var, c.as_ptr()) };
+                    }
+                }
+            }
+        }
+
+        if let Some(user_miss) = user_missing {
+            for um in user_miss {
+                if um.col == field.name().as_str() {
+                    if is_str_col[j] {
+                        for val in &um.values {
+                            let val_string = if val.fract() == 0.0 {
+                                format!("{:.0}", val)
+                            } else {
+                                val.to_string()
+                            };
+                            if let Ok(c_val) = CString::new(val_string.as_str()) {
+                                unsafe {
+                                    readstat_variable_add_missing_string_value(var, c_val.as_ptr());
+                                }
+                            }
+                        }
+                    } else {
+                        for &val in &um.values {
+                            unsafe {
+                                readstat_variable_add_missing_double_value(var, val);
+                            }
+                        }
+                        if let Some((low, high)) = um.range {
+                            unsafe {
+                                readstat_variable_add_missing_double_range(var, low, high);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(val_labs) = value_labels {
+            for vl in val_labs {
+                if vl.col == field.name().as_str() && !vl.labels.is_empty() {
+                    let label_set_name = format!("{}_labels", field.name());
+                    let c_label_set_name = CString::new(label_set_name.as_str())?;
+
+                    let label_set =
+                        unsafe { readstat_add_label_set(writer, typ, c_label_set_name.as_ptr()) };
+
+                    if !label_set.is_null() {
+                        let mut c_strings = Vec::new();
+
+                        for (value, label) in &vl.labels {
+                            if let Ok(c_label) = to_target_cstring(label, encoding) {
+                                if is_str_col[j] {
+                                    if let Ok(c_val) = to_target_cstring(value, encoding) {
+                                        unsafe {
+                                            readstat_label_string_value(
+                                                label_set,
+                                                c_val.as_ptr(),
+                                                c_label.as_ptr(),
+                                            );
+                                        }
+                                        c_strings.push(c_val);
+                                    }
+                                } else if let Ok(num_val) = value.parse::<f64>() {
+                                    unsafe {
+                                        readstat_label_double_value(
+                                            label_set,
+                                            num_val,
+                                            c_label.as_ptr(),
+                                        );
+                                    }
+                                }
+                                c_strings.push(c_label);
+                            }
+                        }
+
+                        unsafe {
+                            readstat_variable_set_label_set(var, label_set);
+                        }
+
+                        _keep_label_sets.push((label_set, c_strings));
+                    }
+                }
+            }
+        }
+
+        _keep_names.push(cname);
+        rvars.push(var);
+    }
+
+    unsafe {
+        let rc = readstat_begin_writing_por(
+            writer,
+            sink as *mut W as *mut c_void,
+            scan.total_rows.try_into().expect("row count overflow"),
+        );
+        if rc != 0 {
+            readstat_writer_free(writer);
+            return Err(anyhow!("readstat_begin_writing_por failed with rc={}", rc));
+        }
+    }
+
+    let reader = open_ipc_reader(ipc_bytes)?;
+    for b in reader {
+        let b = b.map_err(crate::core::describe_ipc_decode_err)?;
+        for i in 0..b.num_rows() {
+            unsafe {
+                let rc = readstat_begin_row(writer);
+                if rc != 0 {
+                    readstat_writer_free(writer);
+                    return Err(anyhow!("readstat_begin_row failed with rc={}", rc));
+                }
+            }
+
+            for (j, arr) in b.columns().iter().enumerate() {
+                if is_str_col[j] {
+                    if let Some(s) = get_string_value(arr.as_ref(), i) {
+                        unsafe {
+                            match to_target_cstring(s, encoding) {
+                                Ok(cs) => {
+                                    let rc =
+                                        readstat_insert_string_value(writer, rvars[j], cs.as_ptr());
+                                    if rc != 0 {
+                                        readstat_writer_free(writer);
+                                        return Err(anyhow!(
+                                            "insert_string_value failed with rc={}",
+                                            rc
+                                        ));
+                                    }
+                                }
+                                Err(_) => {
+                                    let rc = readstat_insert_missing_value(writer, rvars[j]);
+                                    if rc != 0 {
+                                        readstat_writer_free(writer);
+                                        return Err(anyhow!(
+                                            "insert_missing_value (embedded NUL) failed with rc={}",
+                                            rc
+                                        ));
+                                    }
+                                }
+                            }
+                        }
+                    } else {
+                        unsafe {
+                            let rc = readstat_insert_missing_value(writer, rvars[j]);
+                            if rc != 0 {
+                                readstat_writer_free(writer);
+                                return Err(anyhow!(
+                                    "insert_missing_value (null) failed with rc={}",
+                                    rc
+                                ));
+                            }
+                        }
+                    }
+                } else if let Some(v) = as_f64_opt(arr.as_ref(), i) {
+                    unsafe {
+                        let rc = readstat_insert_double_value(writer, rvars[j], v);
+                        if rc != 0 {
+                            readstat_writer_free(writer);
+                            return Err(anyhow!("insert_double_value failed with rc={}", rc));
+                        }
+                    }
+                } else {
+                    unsafe {
+                        let rc = readstat_insert_missing_value(writer, rvars[j]);
+                        if rc != 0 {
+                            readstat_writer_free(writer);
+                            return Err(anyhow!(
+                                "insert_missing_value (double) failed with rc={}",
+                                rc
+                            ));
+                        }
+                    }
+                }
+            }
+
+            unsafe {
+                let rc = readstat_end_row(writer);
+                if rc != 0 {
+                    readstat_writer_free(writer);
+                    return Err(anyhow!("readstat_end_row failed with rc={}", rc));
+                }
+            }
+        }
+    }
+
+    unsafe {
+        let rc = readstat_end_writing(writer);
+        if rc != 0 {
+            readstat_writer_free(writer);
+            return Err(anyhow!("readstat_end_writing failed with rc={}", rc));
+        }
+        readstat_writer_free(writer);
+    }
+
+    Ok(())
+}
+
+/// Shared argument conversion for `df_write_sav_file` / `df_write_sav_bytes`:
+/// turn the Python-friendly dicts/labels into the structures
+/// `write_spss_minimal` expects. The raw IPC bytes themselves are handed to
+/// `write_spss_minimal` unchanged so it can stream them in two passes rather
+/// than decoding the whole table up front.
+#[allow(clippy::type_complexity)]
+fn prepare_write_args(
+    encoding: Option<&str>,
+    user_missing: Option<&[HashMap<String, PyObject>]>,
+    value_labels: Option<&[HashMap<String, PyObject>]>,
+) -> PyResult<(
+    Option<&'static Encoding>,
+    Option<Vec<UserMissingInfo>>,
+    Option<Vec<ValueLabelsInfo>>,
+)> {
+    let target_encoding = match encoding {
+        Some(label) => Some(Encoding::for_label(label.as_bytes()).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("Unknown encoding label: {label}"))
+        })?),
+        None => None,
+    };
+
+    // Convert user_missing from Python-friendly dicts
+    let user_missing_converted: Option<Vec<UserMissingInfo>> = user_missing.map(|um_vec| {
+        Python::with_gil(|py| {
+            um_vec
+                .iter()
+                .filter_map(|um_dict| {
+                    let col = um_dict.get("col")?.extract::<String>(py).ok()?;
+
+                    let values = um_dict
+                        .get("values")
+                        .and_then(|v| v.extract::<Vec<f64>>(py).ok())
+                        .unwrap_or_default();
+
+                    let range = um_dict
+                        .get("range")
+                        .and_then(|r| r.extract::<(f64, f64)>(py).ok());
+
+                    Some(UserMissingInfo { col, values, range })
+                })
+                .collect()
+        })
+    });
+
+    // Convert value_labels from Python-friendly dicts
+    let value_labels_converted: Option<Vec<ValueLabelsInfo>> = value_labels.map(|vl_vec| {
+        Python::with_gil(|py| {
+            vl_vec
+                .iter()
+                .filter_map(|vl_dict| {
+                    let col = vl_dict.get("col")?.extract::<String>(py).ok()?;
+                    let labels = vl_dict
+                        .get("labels")?
+                        .extract::<HashMap<String, String>>(py)
+                        .ok()?;
+                    Some(ValueLabelsInfo { col, labels })
+                })
+                .collect()
+        })
+    });
+
+    Ok((
+        target_encoding,
+        user_missing_converted,
+        value_labels_converted,
+    ))
+}
+
 #[pyfunction]
-#[pyo3(signature = (ipc_bytes, out_path, file_label=None, compress="byte", var_labels=None, user_missing=None, value_labels=None))]
+#[pyo3(signature = (
+    ipc_bytes,
+    out_path,
+    file_label=None,
+    compress="byte",
+    encoding=None,
+    var_labels=None,
+    user_missing=None,
+    value_labels=None,
+    var_measures=None,
+    var_display_widths=None,
+    var_alignments=None,
+    var_formats=None
+))]
+#[allow(clippy::too_many_arguments)]
 pub fn df_write_sav_file(
+    py: Python<'_>,
     ipc_bytes: Bound<'_, PyBytes>,
     out_path: &str,
     file_label: Option<&str>,
     compress: &str,
+    encoding: Option<&str>,
     var_labels: Option<HashMap<String, String>>,
     user_missing: Option<Vec<HashMap<String, PyObject>>>,
     value_labels: Option<Vec<HashMap<String, PyObject>>>,
+    var_measures: Option<HashMap<String, String>>,
+    var_display_widths: Option<HashMap<String, i32>>,
+    var_alignments: Option<HashMap<String, String>>,
+    var_formats: Option<HashMap<String, String>>,
 ) -> PyResult<()> {
+    let (target_encoding, user_missing_converted, value_labels_converted) =
+        prepare_write_args(encoding, user_missing.as_deref(), value_labels.as_deref())?;
+
     let buf = ipc_bytes.as_bytes();
-    let batches = ipc_to_batches(buf).map_err(|e| {
-        pyo3::exceptions::PyRuntimeError::new_err(format!("Arrow IPC decode failed: {}", e))
-    })?;
+    let mut outfile = File::create(Path::new(out_path))?;
+    // Everything write_spss_minimal touches from here on is an owned Rust
+    // value or a plain byte slice (no PyObject/Py<...>), so it's safe to
+    // release the GIL for the duration of serialization + compression.
+    py.allow_threads(|| {
+        write_spss_minimal(
+            IpcSource::Bytes(buf),
+            &mut outfile,
+            file_label,
+            compress,
+            target_encoding,
+            var_labels.as_ref(),
+            user_missing_converted.as_deref(),
+            value_labels_converted.as_deref(),
+            var_measures.as_ref(),
+            var_display_widths.as_ref(),
+            var_alignments.as_ref(),
+            var_formats.as_ref(),
+        )
+    })
+    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("df_write_sav_file: {}", e)))
+}
 
-    // Convert user_missing from Python-friendly dicts
-    let user_missing_converted: Option<Vec<UserMissingInfo>> =
-        user_missing.as_ref().map(|um_vec| {
-            Python::with_gil(|py| {
-                um_vec
-                    .iter()
-                    .filter_map(|um_dict| {
-                        let col = um_dict.get("col")?.extract::<String>(py).ok()?;
-
-                        let values = um_dict
-                            .get("values")
-                            .and_then(|v| v.extract::<Vec<f64>>(py).ok())
-                            .unwrap_or_default();
-
-                        let range = um_dict
-                            .get("range")
-                            .and_then(|r| r.extract::<(f64, f64)>(py).ok());
-
-                        Some(UserMissingInfo { col, values, range })
-                    })
-                    .collect()
-            })
-        });
+/// Same as `df_write_sav_file`, but serializes into an in-memory buffer and
+/// returns the `.sav`/`.zsav` bytes directly, for callers that don't want to
+/// round-trip through the filesystem (web services, object-store uploads, tests).
+#[pyfunction]
+#[pyo3(signature = (
+    ipc_bytes,
+    file_label=None,
+    compress="byte",
+    encoding=None,
+    var_labels=None,
+    user_missing=None,
+    value_labels=None,
+    var_measures=None,
+    var_display_widths=None,
+    var_alignments=None,
+    var_formats=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn df_write_sav_bytes<'py>(
+    py: Python<'py>,
+    ipc_bytes: Bound<'_, PyBytes>,
+    file_label: Option<&str>,
+    compress: &str,
+    encoding: Option<&str>,
+    var_labels: Option<HashMap<String, String>>,
+    user_missing: Option<Vec<HashMap<String, PyObject>>>,
+    value_labels: Option<Vec<HashMap<String, PyObject>>>,
+    var_measures: Option<HashMap<String, String>>,
+    var_display_widths: Option<HashMap<String, i32>>,
+    var_alignments: Option<HashMap<String, String>>,
+    var_formats: Option<HashMap<String, String>>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let (target_encoding, user_missing_converted, value_labels_converted) =
+        prepare_write_args(encoding, user_missing.as_deref(), value_labels.as_deref())?;
 
-    // Convert value_labels from Python-friendly dicts
-    let value_labels_converted: Option<Vec<ValueLabelsInfo>> =
-        value_labels.as_ref().map(|vl_vec| {
-            Python::with_gil(|py| {
-                vl_vec
-                    .iter()
-                    .filter_map(|vl_dict| {
-                        let col = vl_dict.get("col")?.extract::<String>(py).ok()?;
-                        let labels = vl_dict
-                            .get("labels")?
-                            .extract::<HashMap<String, String>>(py)
-                            .ok()?;
-                        Some(ValueLabelsInfo { col, labels })
-                    })
-                    .collect()
-            })
-        });
-
-    write_spss_minimal(
-        &batches,
-        out_path,
+    let ipc_buf = ipc_bytes.as_bytes();
+    let mut buf: Vec<u8> = Vec::new();
+    // See df_write_sav_file: everything crossing into the closure is an owned
+    // value or a plain byte slice, so the GIL can be released while we
+    // serialize and (optionally) compress.
+    py.allow_threads(|| {
+        write_spss_minimal(
+            IpcSource::Bytes(ipc_buf),
+            &mut buf,
+            file_label,
+            compress,
+            target_encoding,
+            var_labels.as_ref(),
+            user_missing_converted.as_deref(),
+            value_labels_converted.as_deref(),
+            var_measures.as_ref(),
+            var_display_widths.as_ref(),
+            var_alignments.as_ref(),
+            var_formats.as_ref(),
+        )
+    })
+    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("df_write_sav_bytes: {}", e)))?;
+
+    Ok(PyBytes::new_bound(py, &buf))
+}
+
+/// Chunk-aware sibling of `df_write_sav_file`: instead of one in-memory IPC
+/// buffer, `chunks` is any Python iterable (a generator, typically) of
+/// `bytes` objects that, concatenated in order, form a single Arrow IPC
+/// stream. This lets a producer hand over the data incrementally instead of
+/// materializing the whole serialized table up front.
+///
+/// `write_spss_minimal` still needs two independent passes over the data
+/// (string widths + row count, then the rows themselves) and a generator can
+/// only be drained once, so this spills the chunks to a temp file first —
+/// the same trick a shuffle-writer uses to stream batches to disk — and
+/// streams that file for both passes instead. Peak memory is bounded to one
+/// chunk (while draining `chunks`) plus one decoded `RecordBatch` (during the
+/// two passes), never the whole table.
+#[pyfunction]
+#[pyo3(signature = (
+    chunks,
+    out_path,
+    file_label=None,
+    compress="byte",
+    encoding=None,
+    var_labels=None,
+    user_missing=None,
+    value_labels=None,
+    var_measures=None,
+    var_display_widths=None,
+    var_alignments=None,
+    var_formats=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn df_write_sav_file_chunked(
+    py: Python<'_>,
+    chunks: Bound<'_, PyAny>,
+    out_path: &str,
+    file_label: Option<&str>,
+    compress: &str,
+    encoding: Option<&str>,
+    var_labels: Option<HashMap<String, String>>,
+    user_missing: Option<Vec<HashMap<String, PyObject>>>,
+    value_labels: Option<Vec<HashMap<String, PyObject>>>,
+    var_measures: Option<HashMap<String, String>>,
+    var_display_widths: Option<HashMap<String, i32>>,
+    var_alignments: Option<HashMap<String, String>>,
+    var_formats: Option<HashMap<String, String>>,
+) -> PyResult<()> {
+    let (target_encoding, user_missing_converted, value_labels_converted) =
+        prepare_write_args(encoding, user_missing.as_deref(), value_labels.as_deref())?;
+
+    // Draining the generator requires the GIL throughout, so this (unlike
+    // df_write_sav_file/df_write_sav_bytes) can't release it for the whole
+    // call — only the chunks are Python-visible here, everything downstream
+    // of the spill file is plain Rust.
+    let spill_path = std::env::temp_dir().join(format!(
+        "svyreadstat_sav_chunk_spill_{}_{:x}.arrow",
+        std::process::id(),
+        chunks.as_ptr() as usize
+    ));
+    {
+        let mut spill = File::create(&spill_path)?;
+        for item in chunks.iter()? {
+            let bytes: Vec<u8> = item?.extract()?;
+            spill.write_all(&bytes)?;
+        }
+    }
+
+    let mut outfile = File::create(Path::new(out_path))?;
+    let result = write_spss_minimal(
+        IpcSource::Path(&spill_path),
+        &mut outfile,
         file_label,
         compress,
+        target_encoding,
         var_labels.as_ref(),
         user_missing_converted.as_deref(),
         value_labels_converted.as_deref(),
-    )
-    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("df_write_sav_file: {}", e)))
+        var_measures.as_ref(),
+        var_display_widths.as_ref(),
+        var_alignments.as_ref(),
+        var_formats.as_ref(),
+    );
+
+    let _ = std::fs::remove_file(&spill_path);
+    result.map_err(|e| {
+        pyo3::exceptions::PyRuntimeError::new_err(format!("df_write_sav_file_chunked: {}", e))
+    })
+}
+
+/// Write an SPSS portable (`.por`) file from the same Arrow IPC + metadata
+/// contract as `df_write_sav_file`, via readstat's `readstat_begin_writing_por`.
+/// POR has no compression and no Variable-View-era metadata, so this takes a
+/// narrower set of optional channels than the SAV writer.
+#[pyfunction]
+#[pyo3(signature = (
+    ipc_bytes,
+    out_path,
+    file_label=None,
+    encoding=None,
+    var_labels=None,
+    user_missing=None,
+    value_labels=None,
+    var_formats=None,
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn df_write_por_file(
+    py: Python<'_>,
+    ipc_bytes: Bound<'_, PyBytes>,
+    out_path: &str,
+    file_label: Option<&str>,
+    encoding: Option<&str>,
+    var_labels: Option<HashMap<String, String>>,
+    user_missing: Option<Vec<HashMap<String, PyObject>>>,
+    value_labels: Option<Vec<HashMap<String, PyObject>>>,
+    var_formats: Option<HashMap<String, String>>,
+) -> PyResult<()> {
+    let (target_encoding, user_missing_converted, value_labels_converted) =
+        prepare_write_args(encoding, user_missing.as_deref(), value_labels.as_deref())?;
+
+    let buf = ipc_bytes.as_bytes();
+    let mut outfile = File::create(Path::new(out_path))?;
+    py.allow_threads(|| {
+        write_por_minimal(
+            buf,
+            &mut outfile,
+            file_label,
+            target_encoding,
+            var_labels.as_ref(),
+            user_missing_converted.as_deref(),
+            value_labels_converted.as_deref(),
+            var_formats.as_ref(),
+        )
+    })
+    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("df_write_por_file: {}", e)))
+}
+
+/// Same as `df_write_por_file`, but serializes into an in-memory buffer and
+/// returns the `.por` bytes directly.
+#[pyfunction]
+#[pyo3(signature = (
+    ipc_bytes,
+    file_label=None,
+    encoding=None,
+    var_labels=None,
+    user_missing=None,
+    value_labels=None,
+    var_formats=None,
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn df_write_por_bytes<'py>(
+    py: Python<'py>,
+    ipc_bytes: Bound<'_, PyBytes>,
+    file_label: Option<&str>,
+    encoding: Option<&str>,
+    var_labels: Option<HashMap<String, String>>,
+    user_missing: Option<Vec<HashMap<String, PyObject>>>,
+    value_labels: Option<Vec<HashMap<String, PyObject>>>,
+    var_formats: Option<HashMap<String, String>>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let (target_encoding, user_missing_converted, value_labels_converted) =
+        prepare_write_args(encoding, user_missing.as_deref(), value_labels.as_deref())?;
+
+    let ipc_buf = ipc_bytes.as_bytes();
+    let mut buf: Vec<u8> = Vec::new();
+    py.allow_threads(|| {
+        write_por_minimal(
+            ipc_buf,
+            &mut buf,
+            file_label,
+            target_encoding,
+            var_labels.as_ref(),
+            user_missing_converted.as_deref(),
+            value_labels_converted.as_deref(),
+            var_formats.as_ref(),
+        )
+    })
+    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("df_write_por_bytes: {}", e)))?;
+
+    Ok(PyBytes::new_bound(py, &buf))
 }
@@ -16,11 +16,12 @@ const RS_USER_ABORT: readstat_error_t = readstat_error_e_READSTAT_ERROR_USER_ABO
 
 /// Parse a Stata .dta file into Arrow IPC format
 #[inline]
-fn parse_dta_impl(
+pub(crate) fn parse_dta_impl(
     data_path: &str,
     rows_skip: usize,
     n_max: Option<usize>,
     cols_skip: Option<Vec<String>>,
+    apply_value_labels: bool,
 ) -> Result<(Vec<u8>, crate::core::MetaOut)> {
     let mut ctx = ParseCtx {
         cols: Vec::with_capacity(64), // Pre-allocate for typical files
@@ -36,13 +37,16 @@ fn parse_dta_impl(
         n_max,
         n_rows_seen: 0,
         n_rows_emitted: 0,
+        last_emitted_row: None,
         label_sets: HashMap::with_capacity(32), // Pre-allocate
         file_label: None,
         last_err: None,
         tagged: HashMap::with_capacity(16), // Pre-allocate
         notes: Vec::with_capacity(8),
         detect_tagged: true,
+        user_na: false, // Stata missings are handled via detect_tagged instead
         row_capacity: None, // Filled in metadata callback
+        apply_value_labels,
     };
 
     unsafe {
@@ -76,16 +80,19 @@ fn parse_dta_impl(
 }
 
 #[pyfunction]
-#[pyo3(signature = (data_path, cols_skip=None, n_max=None, rows_skip=0))]
+#[pyo3(signature = (data_path, cols_skip=None, n_max=None, rows_skip=0, apply_value_labels=false))]
 pub fn df_parse_dta_file<'py>(
     py: Python<'py>,
     data_path: &str,
     cols_skip: Option<Vec<String>>,
     n_max: Option<usize>,
     rows_skip: usize,
+    apply_value_labels: bool,
 ) -> PyResult<(PyObject, String)> {
     // Release GIL during parsing for better Python concurrency
-    let result = py.allow_threads(|| parse_dta_impl(data_path, rows_skip, n_max, cols_skip));
+    let result = py.allow_threads(|| {
+        parse_dta_impl(data_path, rows_skip, n_max, cols_skip, apply_value_labels)
+    });
 
     let (ipc, meta) =
         result.map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
@@ -19,17 +19,20 @@ use arrow::datatypes::{
     DataType, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type, UInt64Type,
     UInt8Type,
 };
-use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::ipc::reader::{FileReader, IpcReadOptions, StreamReader};
 use arrow::record_batch::RecordBatch;
 
 use readstat_sys::{
-    readstat_add_variable, readstat_begin_row, readstat_begin_writing_dta, readstat_end_row,
-    readstat_end_writing, readstat_insert_double_value, readstat_insert_missing_value,
-    readstat_insert_string_value, readstat_set_data_writer,
+    readstat_add_label_set, readstat_add_variable, readstat_begin_row,
+    readstat_begin_writing_dta, readstat_end_row, readstat_end_writing,
+    readstat_insert_double_value, readstat_insert_missing_value, readstat_insert_string_value,
+    readstat_insert_tagged_missing_value, readstat_label_double_value, readstat_label_set_t,
+    readstat_set_data_writer,
     readstat_type_e_READSTAT_TYPE_DOUBLE as T_DOUBLE,
     readstat_type_e_READSTAT_TYPE_STRING as T_STRING, readstat_variable_set_label,
-    readstat_variable_t, readstat_writer_free, readstat_writer_init,
-    readstat_writer_set_file_format_version, readstat_writer_set_file_label,
+    readstat_variable_set_label_set, readstat_variable_t, readstat_writer_free,
+    readstat_writer_init, readstat_writer_set_file_format_version,
+    readstat_writer_set_file_label,
 };
 
 unsafe extern "C" fn data_writer_cb(
@@ -48,17 +51,27 @@ unsafe extern "C" fn data_writer_cb(
     }
 }
 
+// Decoding via `IpcReadOptions` (rather than a bare `None`) rather than the
+// plain constructors lets `FileReader`/`StreamReader` transparently inflate
+// LZ4_FRAME/ZSTD-compressed body buffers, as long as the `arrow` dependency
+// is built with its "lz4"/"zstd" features — the Python layer can compress
+// batches before handing them to `df_write_dta_file` to shrink large
+// transfers, so this path needs to tolerate either framing. If those Cargo
+// features aren't actually enabled, `describe_ipc_decode_err` below turns
+// the resulting Arrow decode failure into a message that says so, rather
+// than leaving it as an opaque error.
 fn ipc_to_batches(buf: &[u8]) -> Result<Vec<RecordBatch>> {
     let mut batches = Vec::new();
+    let options = IpcReadOptions::default();
     if buf.starts_with(b"ARROW1") {
-        let mut fr = FileReader::try_new(Cursor::new(buf), None)?;
+        let mut fr = FileReader::try_new_with_options(Cursor::new(buf), options)?;
         for b in fr.by_ref() {
-            batches.push(b?);
+            batches.push(b.map_err(crate::core::describe_ipc_decode_err)?);
         }
     } else {
-        let mut sr = StreamReader::try_new(Cursor::new(buf), None)?;
+        let mut sr = StreamReader::try_new_with_options(Cursor::new(buf), options)?;
         while let Some(res) = sr.next() {
-            batches.push(res?);
+            batches.push(res.map_err(crate::core::describe_ipc_decode_err)?);
         }
     }
     Ok(batches)
@@ -145,6 +158,24 @@ struct StringColStats {
     has_nul: bool,
 }
 
+/// Value labels (`{code: label}`) for one numeric column, written as a
+/// Stata value-label set. Stata value labels only attach to numeric
+/// variables, so columns backed by string data are skipped.
+#[derive(Debug, Clone)]
+struct ValueLabelsInfo {
+    col: String,
+    labels: HashMap<String, String>,
+}
+
+/// Stata extended/tagged missing values (`.a`-`.z`) to restore at specific
+/// rows of a numeric column on write, mirroring what `ParseCtx.tagged`
+/// captures on read (see `TaggedSpec` in core.rs).
+#[derive(Debug, Clone)]
+struct TaggedMissingInfo {
+    col: String,
+    by_row: HashMap<usize, u8>,
+}
+
 fn compute_string_metadata(batches: &[RecordBatch]) -> Vec<Option<StringColStats>> {
     if batches.is_empty() {
         return Vec::new();
@@ -177,6 +208,289 @@ fn compute_string_metadata(batches: &[RecordBatch]) -> Vec<Option<StringColStats
     all_stats
 }
 
+// Stata's own sentinel for the numeric system-missing value ("."), used by
+// ReadStat's reader to recognize a missing double on the way back in.
+const STATA_DOUBLE_MISSING: f64 = 8.988465674311579e+307;
+const STRL_TYPE_CODE: u16 = 32768;
+const DOUBLE_TYPE_CODE: u16 = 65526;
+
+struct DtaWidths {
+    varname: usize,
+    format: usize,
+    value_label_name: usize,
+    variable_label: usize,
+    label_len_prefix: usize,
+}
+
+fn dta_widths(version: i32) -> DtaWidths {
+    if version >= 118 {
+        DtaWidths {
+            varname: 129,
+            format: 57,
+            value_label_name: 129,
+            variable_label: 321,
+            label_len_prefix: 2,
+        }
+    } else {
+        DtaWidths {
+            varname: 33,
+            format: 49,
+            value_label_name: 33,
+            variable_label: 81,
+            label_len_prefix: 1,
+        }
+    }
+}
+
+/// NUL-pads (or truncates) `s` into exactly `width` bytes, as Stata's 117+
+/// fixed-width name/label/format tables require.
+fn write_fixed(buf: &mut Vec<u8>, s: &str, width: usize) {
+    let bytes = s.as_bytes();
+    let n = bytes.len().min(width.saturating_sub(1));
+    buf.extend_from_slice(&bytes[..n]);
+    buf.resize(buf.len() + (width - n), 0u8);
+}
+
+/// Hand-rolled Stata 117/118 `.dta` writer used only when at least one
+/// string column exceeds `strl_threshold`. ReadStat's C writer has no
+/// concept of strL reference pairs, so once a file needs one, this writer
+/// assembles the whole `<stata_dta>` container itself: fixed-width
+/// metadata tables, row data with `(v, o)` references standing in for long
+/// strings, and a trailing `<strls>` section of GSO records that the
+/// references point at. Short-string/double columns in the same file are
+/// written inline exactly like the readstat path above does.
+///
+/// The in-data/GSO `(v, o)` pair is always 8 bytes total, split 2+6 for both
+/// format 117 and 118 (matching what ReadStat's own parser expects) rather
+/// than the 4+5 split that would overflow the 8-byte slot. The wider 4-byte
+/// `v` doesn't arrive until format 119, which raised Stata's 32,767-variable
+/// limit and isn't supported by this writer.
+fn write_stata_strl_native(
+    batches: &[RecordBatch],
+    out_path: &str,
+    file_label: Option<&str>,
+    version: i32,
+    var_labels: Option<&HashMap<String, String>>,
+    is_str_col: &[bool],
+    str_stats: &[Option<StringColStats>],
+    strl_threshold: i32,
+) -> Result<()> {
+    if version < 117 {
+        return Err(anyhow!(
+            "strL columns require Stata format 117 or newer (got {version}); \
+             truncate the offending strings to {strl_threshold} bytes or raise `version`"
+        ));
+    }
+
+    let widths = dta_widths(version);
+    let schema = batches[0].schema();
+    let ncols = schema.fields().len();
+
+    let mut needs_strl = vec![false; ncols];
+    let mut str_width = vec![0usize; ncols];
+    for j in 0..ncols {
+        if is_str_col[j] {
+            let stats = str_stats[j].unwrap_or(StringColStats {
+                max_len: 1,
+                has_nul: false,
+            });
+            if stats.max_len as i32 > strl_threshold {
+                needs_strl[j] = true;
+            } else {
+                str_width[j] = std::cmp::max(1, std::cmp::min(2045, stats.max_len));
+            }
+        }
+    }
+
+    let mut buf: Vec<u8> = Vec::new();
+    buf.extend_from_slice(b"<stata_dta>");
+
+    buf.extend_from_slice(b"<header>");
+    buf.extend_from_slice(format!("<release>{}</release>", version).as_bytes());
+    buf.extend_from_slice(b"<byteorder>LSF</byteorder>");
+    buf.extend_from_slice(b"<K>");
+    buf.extend_from_slice(&(ncols as u16).to_le_bytes());
+    buf.extend_from_slice(b"</K>");
+    let total_rows: u32 = batches.iter().map(|b| b.num_rows() as u32).sum();
+    buf.extend_from_slice(b"<N>");
+    buf.extend_from_slice(&total_rows.to_le_bytes());
+    buf.extend_from_slice(b"</N>");
+    buf.extend_from_slice(b"<label>");
+    {
+        let lbl_bytes = file_label.unwrap_or("").as_bytes();
+        let n = lbl_bytes.len().min(if widths.label_len_prefix == 1 {
+            255
+        } else {
+            u16::MAX as usize
+        });
+        if widths.label_len_prefix == 1 {
+            buf.push(n as u8);
+        } else {
+            buf.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        buf.extend_from_slice(&lbl_bytes[..n]);
+    }
+    buf.extend_from_slice(b"</label>");
+    buf.extend_from_slice(b"<timestamp>");
+    {
+        // No date-formatting crate is vendored here and the timestamp's
+        // content isn't interpreted by readers, so a fixed placeholder
+        // keeps the writer deterministic.
+        let ts: &[u8] = b"01 Jan 2024 00:00";
+        buf.push(ts.len() as u8);
+        buf.extend_from_slice(ts);
+    }
+    buf.extend_from_slice(b"</timestamp>");
+    buf.extend_from_slice(b"</header>");
+
+    // <map> is written as a zeroed placeholder and patched once every
+    // other section's offset is known.
+    let map_tag_start = buf.len() as u64;
+    buf.extend_from_slice(b"<map>");
+    let map_values_start = buf.len();
+    buf.resize(buf.len() + 14 * 8, 0u8);
+    buf.extend_from_slice(b"</map>");
+
+    let mut offsets = [0u64; 14];
+    offsets[0] = 0;
+    offsets[1] = map_tag_start;
+
+    offsets[2] = buf.len() as u64;
+    buf.extend_from_slice(b"<variable_types>");
+    for j in 0..ncols {
+        let code: u16 = if needs_strl[j] {
+            STRL_TYPE_CODE
+        } else if is_str_col[j] {
+            str_width[j] as u16
+        } else {
+            DOUBLE_TYPE_CODE
+        };
+        buf.extend_from_slice(&code.to_le_bytes());
+    }
+    buf.extend_from_slice(b"</variable_types>");
+
+    offsets[3] = buf.len() as u64;
+    buf.extend_from_slice(b"<varnames>");
+    for field in schema.fields() {
+        write_fixed(&mut buf, field.name(), widths.varname);
+    }
+    buf.extend_from_slice(b"</varnames>");
+
+    offsets[4] = buf.len() as u64;
+    buf.extend_from_slice(b"<sortlist>");
+    buf.resize(buf.len() + (ncols + 1) * 2, 0u8);
+    buf.extend_from_slice(b"</sortlist>");
+
+    offsets[5] = buf.len() as u64;
+    buf.extend_from_slice(b"<formats>");
+    for j in 0..ncols {
+        let fmt = if needs_strl[j] {
+            "%9s".to_string()
+        } else if is_str_col[j] {
+            format!("%{}s", str_width[j])
+        } else {
+            "%9.0g".to_string()
+        };
+        write_fixed(&mut buf, &fmt, widths.format);
+    }
+    buf.extend_from_slice(b"</formats>");
+
+    offsets[6] = buf.len() as u64;
+    buf.extend_from_slice(b"<value_label_names>");
+    for _ in 0..ncols {
+        write_fixed(&mut buf, "", widths.value_label_name);
+    }
+    buf.extend_from_slice(b"</value_label_names>");
+
+    offsets[7] = buf.len() as u64;
+    buf.extend_from_slice(b"<variable_labels>");
+    for field in schema.fields() {
+        let lbl = var_labels
+            .and_then(|m| m.get(field.name()))
+            .map(|s| s.as_str())
+            .unwrap_or("");
+        write_fixed(&mut buf, lbl, widths.variable_label);
+    }
+    buf.extend_from_slice(b"</variable_labels>");
+
+    offsets[8] = buf.len() as u64;
+    buf.extend_from_slice(b"<characteristics>");
+    buf.extend_from_slice(b"</characteristics>");
+
+    offsets[9] = buf.len() as u64;
+    buf.extend_from_slice(b"<data>");
+
+    // 2+6 for both 117 and 118; see the doc comment above on why this isn't
+    // version-dependent until format 119.
+    let v_bytes: usize = 2;
+    let o_bytes: usize = 8 - v_bytes;
+
+    let mut gso_counter = vec![0u64; ncols];
+    let mut gso_records: Vec<(u64, u64, u8, Vec<u8>)> = Vec::new();
+
+    for b in batches {
+        for i in 0..b.num_rows() {
+            for (j, arr) in b.columns().iter().enumerate() {
+                if needs_strl[j] {
+                    let (v, o): (u64, u64) = match get_string_value(arr.as_ref(), i) {
+                        Some(s) => {
+                            gso_counter[j] += 1;
+                            let o = gso_counter[j];
+                            let v = (j + 1) as u64;
+                            let has_nul = s.as_bytes().contains(&0);
+                            let type_byte: u8 = if has_nul { 129 } else { 130 };
+                            gso_records.push((v, o, type_byte, s.as_bytes().to_vec()));
+                            (v, o)
+                        }
+                        None => (0, 0),
+                    };
+                    buf.extend_from_slice(&v.to_le_bytes()[..v_bytes]);
+                    buf.extend_from_slice(&o.to_le_bytes()[..o_bytes]);
+                } else if is_str_col[j] {
+                    let w = str_width[j];
+                    match get_string_value(arr.as_ref(), i) {
+                        Some(s) => write_fixed(&mut buf, s, w),
+                        None => buf.resize(buf.len() + w, 0u8),
+                    }
+                } else {
+                    let v = as_f64_opt(arr.as_ref(), i).unwrap_or(STATA_DOUBLE_MISSING);
+                    buf.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+        }
+    }
+    buf.extend_from_slice(b"</data>");
+
+    offsets[10] = buf.len() as u64;
+    buf.extend_from_slice(b"<strls>");
+    for (v, o, type_byte, bytes) in &gso_records {
+        buf.extend_from_slice(b"GSO");
+        buf.extend_from_slice(&v.to_le_bytes()[..v_bytes]);
+        buf.extend_from_slice(&o.to_le_bytes()[..o_bytes]);
+        buf.push(*type_byte);
+        buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        buf.extend_from_slice(bytes);
+    }
+    buf.extend_from_slice(b"</strls>");
+
+    offsets[11] = buf.len() as u64;
+    buf.extend_from_slice(b"<value_labels>");
+    buf.extend_from_slice(b"</value_labels>");
+
+    offsets[12] = buf.len() as u64;
+    buf.extend_from_slice(b"</stata_dta>");
+
+    offsets[13] = buf.len() as u64;
+
+    for (i, off) in offsets.iter().enumerate() {
+        let at = map_values_start + i * 8;
+        buf[at..at + 8].copy_from_slice(&off.to_le_bytes());
+    }
+
+    std::fs::write(out_path, &buf)?;
+    Ok(())
+}
+
 fn write_stata_minimal(
     batches: &[RecordBatch],
     out_path: &str,
@@ -184,12 +498,70 @@ fn write_stata_minimal(
     version_internal: i32,
     strl_threshold: i32,
     var_labels: Option<&HashMap<String, String>>,
+    value_labels: Option<&[ValueLabelsInfo]>,
+    user_missing: Option<&[TaggedMissingInfo]>,
 ) -> Result<()> {
     if batches.is_empty() {
         let _ = File::create(out_path)?;
         return Ok(());
     }
 
+    let schema = batches[0].schema();
+    let ncols = schema.fields().len();
+
+    let str_stats = compute_string_metadata(batches);
+    let mut is_str_col: Vec<bool> = vec![false; ncols];
+
+    for j in 0..ncols {
+        let dt = batches[0].column(j).data_type();
+        is_str_col[j] = is_text_dt(dt)
+            || matches!(dt, DataType::Dictionary(_, ref v) if is_text_dt(v.as_ref()));
+    }
+
+    let any_needs_strl = (0..ncols).any(|j| {
+        is_str_col[j]
+            && str_stats[j]
+                .map(|s| s.max_len as i32 > strl_threshold)
+                .unwrap_or(false)
+    });
+
+    // ReadStat 1.1.9's own strL writer produces files it can't read back
+    // (rc=5 on reparse), so columns past the threshold are written by our
+    // own native 117/118 writer instead of going through readstat at all.
+    // Short-string/double-only files keep using the readstat path below
+    // unchanged. The native writer doesn't yet persist `value_labels` or
+    // `user_missing` tags (it writes an empty `<value_labels>` section and
+    // always emits plain system-missing), since reproducing Stata's
+    // value-label table and `.a`-`.z` double bit patterns by hand is more
+    // than any strL file in this backlog has needed so far — so refuse the
+    // combination rather than silently dropping the labels/tags.
+    if any_needs_strl {
+        if value_labels.is_some_and(|v| !v.is_empty()) {
+            return Err(anyhow!(
+                "value_labels is not supported together with a strL (long string) column: \
+                 the native strL writer doesn't yet persist a <value_labels> table; \
+                 truncate the offending strings below `strl_threshold` or drop value_labels"
+            ));
+        }
+        if user_missing.is_some_and(|u| !u.is_empty()) {
+            return Err(anyhow!(
+                "user_missing is not supported together with a strL (long string) column: \
+                 the native strL writer doesn't yet persist tagged-missing values; \
+                 truncate the offending strings below `strl_threshold` or drop user_missing"
+            ));
+        }
+        return write_stata_strl_native(
+            batches,
+            out_path,
+            file_label,
+            version_internal,
+            var_labels,
+            &is_str_col,
+            &str_stats,
+            strl_threshold,
+        );
+    }
+
     let writer = unsafe { readstat_writer_init() };
     if writer.is_null() {
         return Err(anyhow!("readstat_writer_init() failed"));
@@ -206,20 +578,9 @@ fn write_stata_minimal(
         }
     }
 
-    let schema = batches[0].schema();
-    let ncols = schema.fields().len();
-
-    let str_stats = compute_string_metadata(batches);
-    let mut is_str_col: Vec<bool> = vec![false; ncols];
-
-    for j in 0..ncols {
-        let dt = batches[0].column(j).data_type();
-        is_str_col[j] = is_text_dt(dt)
-            || matches!(dt, DataType::Dictionary(_, ref v) if is_text_dt(v.as_ref()));
-    }
-
     let mut rvars: Vec<*const readstat_variable_t> = Vec::with_capacity(ncols);
     let mut _keep_names: Vec<CString> = Vec::with_capacity(ncols);
+    let mut _keep_label_sets: Vec<(*const readstat_label_set_t, Vec<CString>)> = Vec::new();
 
     // Define variables
     for (j, field) in schema.fields().iter().enumerate() {
@@ -231,28 +592,6 @@ fn write_stata_minimal(
                 max_len: 1,
                 has_nul: false,
             });
-
-            let needs_strl = (stats.max_len as i32) > strl_threshold;
-
-            if needs_strl {
-                unsafe { readstat_writer_free(writer) };
-                return Err(anyhow!(
-                    "Column '{}' contains strings longer than {} bytes (max: {}).\n\
-                     \n\
-                     strL support is currently unavailable due to a bug in ReadStat library v1.1.9\n\
-                     where written strL files cannot be read back (results in parse error rc=5).\n\
-                     \n\
-                     Workarounds:\n\
-                     1. Truncate strings to {} bytes before writing\n\
-                     2. Use a different file format (e.g., Parquet, CSV)\n\
-                     3. Track github.com/WizardMac/ReadStat for strL fixes in future releases",
-                    field.name(),
-                    strl_threshold,
-                    stats.max_len,
-                    strl_threshold
-                ));
-            }
-
             typ = T_STRING;
             width = std::cmp::max(1, std::cmp::min(2045, stats.max_len));
         }
@@ -279,10 +618,57 @@ fn write_stata_minimal(
             }
         }
 
+        // Value labels (numeric columns only; Stata has no string value labels)
+        if !is_str_col[j] {
+            if let Some(vl_list) = value_labels {
+                if let Some(vl) = vl_list.iter().find(|vl| vl.col == field.name().as_str()) {
+                    if !vl.labels.is_empty() {
+                        let label_set_name = format!("{}_labels", field.name());
+                        let c_label_set_name = CString::new(label_set_name.as_str())?;
+
+                        let label_set = unsafe {
+                            readstat_add_label_set(writer, typ, c_label_set_name.as_ptr())
+                        };
+
+                        if !label_set.is_null() {
+                            let mut c_strings = Vec::new();
+
+                            for (code, label) in &vl.labels {
+                                if let (Ok(num_val), Ok(c_label)) =
+                                    (code.parse::<f64>(), CString::new(label.as_str()))
+                                {
+                                    unsafe {
+                                        readstat_label_double_value(
+                                            label_set,
+                                            num_val,
+                                            c_label.as_ptr(),
+                                        );
+                                    }
+                                    c_strings.push(c_label);
+                                }
+                            }
+
+                            unsafe {
+                                readstat_variable_set_label_set(var, label_set);
+                            }
+
+                            _keep_label_sets.push((label_set, c_strings));
+                        }
+                    }
+                }
+            }
+        }
+
         _keep_names.push(cname);
         rvars.push(var);
     }
 
+    let tagged_by_col: Vec<Option<&TaggedMissingInfo>> = schema
+        .fields()
+        .iter()
+        .map(|f| user_missing.and_then(|list| list.iter().find(|tm| tm.col == f.name().as_str())))
+        .collect();
+
     let mut outfile = File::create(Path::new(out_path))?;
     let total_rows: i64 = batches.iter().map(|b| b.num_rows() as i64).sum();
     unsafe {
@@ -297,6 +683,12 @@ fn write_stata_minimal(
         }
     }
 
+    // `tagged_by_col[j].by_row` is keyed by the row's position in the whole
+    // IPC input (the read side's on_value_cb pushes the global row index),
+    // not its position within whatever RecordBatch it happens to land in —
+    // so the lookup below needs a running counter across batches rather
+    // than the batch-local `i`.
+    let mut grow: usize = 0;
     for b in batches {
         for i in 0..b.num_rows() {
             unsafe {
@@ -348,7 +740,23 @@ fn write_stata_minimal(
                         }
                     }
                 } else {
-                    if let Some(v) = as_f64_opt(arr.as_ref(), i) {
+                    let tag = tagged_by_col[j].and_then(|tm| tm.by_row.get(&grow).copied());
+                    if let Some(tag_byte) = tag {
+                        unsafe {
+                            let rc = readstat_insert_tagged_missing_value(
+                                writer,
+                                rvars[j],
+                                tag_byte as std::os::raw::c_char,
+                            );
+                            if rc != 0 {
+                                readstat_writer_free(writer);
+                                return Err(anyhow!(
+                                    "insert_tagged_missing_value failed with rc={}",
+                                    rc
+                                ));
+                            }
+                        }
+                    } else if let Some(v) = as_f64_opt(arr.as_ref(), i) {
                         unsafe {
                             let rc = readstat_insert_double_value(writer, rvars[j], v);
                             if rc != 0 {
@@ -378,6 +786,7 @@ fn write_stata_minimal(
                     return Err(anyhow!("readstat_end_row failed with rc={}", rc));
                 }
             }
+            grow += 1;
         }
     }
 
@@ -400,9 +809,9 @@ fn write_stata_minimal(
     version,
     file_label=None,
     var_labels_json=None,
-    _value_labels_json=None,
+    value_labels_json=None,
     strl_threshold=2045,
-    _user_missing_json=None
+    user_missing_json=None
 ))]
 pub fn df_write_dta_file(
     ipc_bytes: Bound<'_, PyBytes>,
@@ -410,9 +819,9 @@ pub fn df_write_dta_file(
     version: i32,
     file_label: Option<&str>,
     var_labels_json: Option<&str>,
-    _value_labels_json: Option<&str>,
+    value_labels_json: Option<&str>,
     strl_threshold: i32,
-    _user_missing_json: Option<&str>,
+    user_missing_json: Option<&str>,
 ) -> PyResult<()> {
     let buf = ipc_bytes.as_bytes();
     let batches = ipc_to_batches(buf).map_err(|e| {
@@ -431,6 +840,54 @@ pub fn df_write_dta_file(
         None
     };
 
+    let value_labels: Option<Vec<ValueLabelsInfo>> = if let Some(js) = value_labels_json {
+        let parsed = serde_json::from_str::<HashMap<String, HashMap<String, String>>>(js)
+            .map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "value_labels_json must be a JSON object of {{col: {{code: label}}}}: {e}"
+                ))
+            })?;
+        Some(
+            parsed
+                .into_iter()
+                .map(|(col, labels)| ValueLabelsInfo { col, labels })
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    let user_missing: Option<Vec<TaggedMissingInfo>> = if let Some(js) = user_missing_json {
+        let parsed = serde_json::from_str::<HashMap<String, HashMap<String, String>>>(js)
+            .map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "user_missing_json must be a JSON object of {{col: {{row: tag}}}}: {e}"
+                ))
+            })?;
+        let mut infos = Vec::with_capacity(parsed.len());
+        for (col, by_row_json) in parsed {
+            let mut by_row = HashMap::with_capacity(by_row_json.len());
+            for (row_str, tag) in by_row_json {
+                let row: usize = row_str.parse().map_err(|_| {
+                    pyo3::exceptions::PyValueError::new_err(format!(
+                        "user_missing_json row key '{row_str}' for column '{col}' must be an integer"
+                    ))
+                })?;
+                let tag_byte = tag.as_bytes();
+                if tag_byte.len() != 1 || !tag_byte[0].is_ascii_lowercase() {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "user_missing_json tag '{tag}' for column '{col}' row {row} must be a single letter a-z"
+                    )));
+                }
+                by_row.insert(row, tag_byte[0]);
+            }
+            infos.push(TaggedMissingInfo { col, by_row });
+        }
+        Some(infos)
+    } else {
+        None
+    };
+
     write_stata_minimal(
         &batches,
         out_path,
@@ -438,6 +895,58 @@ pub fn df_write_dta_file(
         version,
         strl_threshold,
         var_labels.as_ref(),
+        value_labels.as_deref(),
+        user_missing.as_deref(),
     )
     .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("df_write_dta_file: {}", e)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::ArrayRef;
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+
+    /// Writes a strL-bearing format-118 file with the native writer and
+    /// reparses it through the regular reader path, guarding against the
+    /// (v, o) reference pair being split wider than the 2+6 bytes Stata (and
+    /// ReadStat's own parser) expect through format 118.
+    #[test]
+    fn test_strl_118_round_trips_through_reader() {
+        let long_value = "x".repeat(3000); // exceeds the default strl_threshold (2045)
+        let schema = Arc::new(Schema::new(vec![Field::new("notes", DataType::Utf8, true)]));
+        let array: ArrayRef = Arc::new(StringArray::from(vec![Some(long_value.as_str()), None]));
+        let batch = RecordBatch::try_new(schema, vec![array]).unwrap();
+        let batches = [batch];
+
+        let out_path = std::env::temp_dir().join(format!(
+            "svyreadstat_test_strl118_{}.dta",
+            std::process::id()
+        ));
+        let out_path_str = out_path.to_str().unwrap().to_string();
+
+        let str_stats = compute_string_metadata(&batches);
+        write_stata_strl_native(
+            &batches,
+            &out_path_str,
+            None,
+            118,
+            None,
+            &[true],
+            &str_stats,
+            2045,
+        )
+        .expect("native strL writer should succeed");
+
+        let result = crate::stata_read::parse_dta_impl(&out_path_str, 0, None, None, false);
+        let _ = std::fs::remove_file(&out_path_str);
+
+        let (ipc, _meta) = result.expect("a 2+6-split strL 118 file should reparse cleanly");
+        let reparsed = ipc_to_batches(&ipc).expect("reparsed IPC should decode");
+        assert_eq!(reparsed.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+        let col = reparsed[0].column(0);
+        assert_eq!(get_string_value(col.as_ref(), 0), Some(long_value.as_str()));
+        assert_eq!(get_string_value(col.as_ref(), 1), None);
+    }
+}
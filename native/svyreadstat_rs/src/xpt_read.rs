@@ -3,12 +3,17 @@ use pyo3::prelude::*;
 use pyo3::types::PyBytes;
 use std::collections::HashMap;
 use std::ffi::CString;
-use std::os::raw::c_void;
+use std::os::raw::{c_char, c_int, c_void};
 
 use readstat_sys::{
     readstat_error_e_READSTAT_ERROR_USER_ABORT as RS_USER_ABORT,
-    readstat_error_e_READSTAT_OK as RS_OK, readstat_parse_xport, readstat_parser_free,
-    readstat_parser_init, readstat_set_error_handler, readstat_set_metadata_handler,
+    readstat_error_e_READSTAT_OK as RS_OK,
+    readstat_io_flags_e_READSTAT_SEEK_CUR as RS_SEEK_CUR,
+    readstat_io_flags_e_READSTAT_SEEK_END as RS_SEEK_END,
+    readstat_io_flags_e_READSTAT_SEEK_SET as RS_SEEK_SET, readstat_off_t, readstat_parse_xport,
+    readstat_parser_free, readstat_parser_init, readstat_set_close_handler,
+    readstat_set_error_handler, readstat_set_io_ctx, readstat_set_metadata_handler,
+    readstat_set_open_handler, readstat_set_read_handler, readstat_set_seek_handler,
     readstat_set_value_handler, readstat_set_value_label_handler, readstat_set_variable_handler,
 };
 
@@ -22,6 +27,7 @@ fn parse_xpt_impl(
     rows_skip: usize,
     n_max: Option<usize>,
     cols_skip: Option<Vec<String>>,
+    apply_value_labels: bool,
 ) -> Result<(Vec<u8>, crate::core::MetaOut)> {
     let mut ctx = ParseCtx {
         cols: Vec::new(),
@@ -31,13 +37,16 @@ fn parse_xpt_impl(
         n_max,
         n_rows_seen: 0,
         n_rows_emitted: 0,
+        last_emitted_row: None,
         label_sets: HashMap::new(),
         file_label: None,
         last_err: None,
         tagged: HashMap::new(),
         notes: Vec::new(),
         detect_tagged: false, // XPT: no tagged-missing semantics
+        user_na: false,       // XPT has no user-defined missing ranges
         row_capacity: None,   // set via on_metadata_cb
+        apply_value_labels,
     };
 
     unsafe {
@@ -72,17 +81,172 @@ fn parse_xpt_impl(
 }
 
 #[pyfunction]
-#[pyo3(signature = (data_path, n_max=None, rows_skip=0, cols_skip=None))]
+#[pyo3(signature = (data_path, n_max=None, rows_skip=0, cols_skip=None, apply_value_labels=false))]
 pub fn df_parse_xpt_file<'py>(
     py: Python<'py>,
     data_path: &str,
     n_max: Option<usize>,
     rows_skip: usize,
     cols_skip: Option<Vec<String>>,
+    apply_value_labels: bool,
 ) -> PyResult<(PyObject, String)> {
-    let (ipc, meta) = parse_xpt_impl(data_path, rows_skip, n_max, cols_skip)
+    let (ipc, meta) = parse_xpt_impl(data_path, rows_skip, n_max, cols_skip, apply_value_labels)
         .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
     let meta_json = serde_json::to_string(&meta).unwrap();
     let pybytes = PyBytes::new_bound(py, &ipc).into_py(py);
     Ok((pybytes, meta_json))
 }
+
+// --- in-memory I/O backend ---------------------------------------------------
+//
+// ReadStat's `readstat_io_t` open/read/seek/close handlers let a parser run
+// against any byte source, not just a path it opens itself. This backend
+// services those callbacks against an owned `Vec<u8>` + cursor so
+// `df_parse_xpt_bytes` can parse XPORT data handed in from `BytesIO` or
+// object storage without spilling to a temp file first.
+struct MemIoCtx {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+unsafe extern "C" fn mem_open_cb(_path: *const c_char, _io_ctx: *mut c_void) -> c_int {
+    // Nothing to open: the buffer is already resident. ReadStat still
+    // requires a handler that reports success before it will read/seek.
+    0
+}
+
+unsafe extern "C" fn mem_close_cb(_io_ctx: *mut c_void) -> c_int {
+    0
+}
+
+unsafe extern "C" fn mem_seek_cb(
+    offset: readstat_off_t,
+    whence: u32,
+    io_ctx: *mut c_void,
+) -> readstat_off_t {
+    if io_ctx.is_null() {
+        return -1;
+    }
+    let ctx = &mut *(io_ctx as *mut MemIoCtx);
+    let len = ctx.buf.len() as readstat_off_t;
+    let new_pos = if whence == RS_SEEK_SET {
+        offset
+    } else if whence == RS_SEEK_CUR {
+        ctx.pos as readstat_off_t + offset
+    } else if whence == RS_SEEK_END {
+        len + offset
+    } else {
+        return -1;
+    };
+    if new_pos < 0 || new_pos > len {
+        return -1;
+    }
+    ctx.pos = new_pos as usize;
+    new_pos
+}
+
+unsafe extern "C" fn mem_read_cb(buf: *mut c_void, nbyte: usize, io_ctx: *mut c_void) -> isize {
+    if io_ctx.is_null() || buf.is_null() {
+        return -1;
+    }
+    let ctx = &mut *(io_ctx as *mut MemIoCtx);
+    let remaining = ctx.buf.len().saturating_sub(ctx.pos);
+    let n = nbyte.min(remaining);
+    if n > 0 {
+        std::ptr::copy_nonoverlapping(ctx.buf[ctx.pos..].as_ptr(), buf as *mut u8, n);
+        ctx.pos += n;
+    }
+    n as isize
+}
+
+fn parse_xpt_bytes_impl(
+    data: Vec<u8>,
+    rows_skip: usize,
+    n_max: Option<usize>,
+    cols_skip: Option<Vec<String>>,
+    apply_value_labels: bool,
+) -> Result<(Vec<u8>, crate::core::MetaOut)> {
+    let mut ctx = ParseCtx {
+        cols: Vec::new(),
+        name_to_idx: HashMap::new(),
+        cols_skip: cols_skip.map(|v| v.into_iter().map(|k| (k, ())).collect()),
+        rows_skip,
+        n_max,
+        n_rows_seen: 0,
+        n_rows_emitted: 0,
+        last_emitted_row: None,
+        label_sets: HashMap::new(),
+        file_label: None,
+        last_err: None,
+        tagged: HashMap::new(),
+        notes: Vec::new(),
+        detect_tagged: false, // XPT: no tagged-missing semantics
+        user_na: false,       // XPT has no user-defined missing ranges
+        row_capacity: None,   // set via on_metadata_cb
+        apply_value_labels,
+    };
+
+    let mut io_ctx = Box::new(MemIoCtx { buf: data, pos: 0 });
+
+    unsafe {
+        let p = readstat_parser_init();
+        if p.is_null() {
+            return Err(anyhow!("readstat_parser_init() failed"));
+        }
+        readstat_set_error_handler(p, Some(on_error_cb));
+        readstat_set_metadata_handler(p, Some(on_metadata_cb));
+        readstat_set_variable_handler(p, Some(on_variable_cb));
+        readstat_set_value_handler(p, Some(on_value_cb));
+        readstat_set_value_label_handler(p, Some(on_value_label_cb));
+
+        readstat_set_open_handler(p, Some(mem_open_cb));
+        readstat_set_close_handler(p, Some(mem_close_cb));
+        readstat_set_seek_handler(p, Some(mem_seek_cb));
+        readstat_set_read_handler(p, Some(mem_read_cb));
+        readstat_set_io_ctx(p, io_ctx.as_mut() as *mut MemIoCtx as *mut c_void);
+
+        // The open handler above ignores the path; it's only present
+        // because `readstat_parse_xport` requires a non-null C string.
+        let dummy_path = CString::new("<memory>")?;
+        let rc = readstat_parse_xport(
+            p,
+            dummy_path.as_ptr(),
+            &mut ctx as *mut _ as *mut c_void,
+        );
+        readstat_parser_free(p);
+
+        let early_ok = ctx
+            .n_max
+            .map(|nm| ctx.n_rows_emitted >= nm)
+            .unwrap_or(false);
+        if rc != RS_OK && !early_ok && rc != RS_USER_ABORT {
+            let msg = ctx.last_err.take().unwrap_or_else(|| format!("rc={rc}"));
+            return Err(anyhow!("Failed to parse XPT bytes: {msg}"));
+        }
+    }
+
+    finalize_to_ipc(ctx)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data, n_max=None, rows_skip=0, cols_skip=None, apply_value_labels=false))]
+pub fn df_parse_xpt_bytes<'py>(
+    py: Python<'py>,
+    data: Bound<'py, PyBytes>,
+    n_max: Option<usize>,
+    rows_skip: usize,
+    cols_skip: Option<Vec<String>>,
+    apply_value_labels: bool,
+) -> PyResult<(PyObject, String)> {
+    let owned = data.as_bytes().to_vec();
+    let result = py.allow_threads(|| {
+        parse_xpt_bytes_impl(owned, rows_skip, n_max, cols_skip, apply_value_labels)
+    });
+
+    let (ipc, meta) =
+        result.map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+    let meta_json = serde_json::to_string(&meta)
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+    let pybytes = PyBytes::new_bound(py, &ipc).into_py(py);
+    Ok((pybytes, meta_json))
+}